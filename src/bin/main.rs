@@ -1,149 +1,376 @@
 use ray_tracer::{
-    random_in_range, vec3_random, vec3_random_in_range, Camera, Color, Dielectric, HitRecord,
-    Hittable, HittableList, Lambertian, Material, Metal, Point3, Ray, Sphere, Vec3,
+    builtin_scene, integrator_from_name, load_scene, render, seed_thread_rng, Color, Crop,
+    ImageFormat, Scene, ToneMap, ToneMapOperator, TransferFunction, BUILTIN_SCENES, INTEGRATORS,
+};
+use std::{
+    env,
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Write},
+    process,
+    str::FromStr,
 };
-use std::{f64::INFINITY, rc::Rc};
 
-fn ray_color(r: Ray, world: &dyn Hittable, depth: i32) -> Color {
-    let mut rec = HitRecord::new();
+const USAGE: &str = "\
+Usage: ray_tracer [OPTIONS] [SCENE_FILE]
 
-    // If we've exceeded the ray bounce limit, gather no more light.
-    if depth <= 0 {
-        return Color::new(0.0, 0.0, 0.0);
-    }
+Renders SCENE_FILE, a TOML scene description, or a built-in scene.
+
+Options:
+  -s, --scene <NAME>         Render a built-in scene instead of a file [default: random]
+  -o, --output <PATH>        Write the image to PATH instead of stdout
+  -f, --format <FORMAT>      Image format: png, ppm (binary), ppm-plain, or exr and hdr
+                             for linear radiance
+                             [default: from the output extension, ppm-plain on stdout]
+      --exposure <EV>        Exposure adjustment in stops [default: 0]
+      --tonemap <OPERATOR>   Tone mapping: clamp, reinhard, reinhard-extended or aces
+                             [default: clamp]
+      --white-point <VALUE>  Radiance that maps to white [default: 1]
+      --transfer <FUNCTION>  Output encoding: gamma2 or srgb [default: gamma2]
+  -W, --width <PIXELS>       Image width; keeps the aspect ratio unless --height is also given
+  -H, --height <PIXELS>      Image height; keeps the aspect ratio unless --width is also given
+  -n, --samples <COUNT>      Samples per pixel
+  -d, --max-depth <COUNT>    Maximum number of ray bounces
+  -i, --integrator <NAME>    Light transport: path, naive, nee (next event estimation
+                             with MIS), spectral (for dispersion), direct, ao, or
+                             normals, albedo and depth for debugging [default: path]
+  -j, --threads <COUNT>      Worker threads [default: all cores]
+      --seed <NUMBER>        Seed the random number generator for a reproducible image
+      --crop <X0,Y0,X1,Y1>   Only render pixels X0..X1, Y0..Y1 from the top-left corner
+  -h, --help                 Print this help
+";
+
+#[derive(Debug, Default, PartialEq)]
+struct Options {
+    scene_file: Option<String>,
+    scene_name: Option<String>,
+    output: Option<String>,
+    format: Option<ImageFormat>,
+    tone_map: ToneMap,
+    width: Option<usize>,
+    height: Option<usize>,
+    samples: Option<usize>,
+    max_depth: Option<i32>,
+    integrator: Option<String>,
+    threads: Option<usize>,
+    seed: Option<u64>,
+    crop: Option<Crop>,
+    help: bool,
+}
 
-    if world.hit(r, 0.001, INFINITY, &mut rec) {
-        if let Some(in_mat_rec) = rec.material.clone() {
-            let mat_rec = in_mat_rec.scatter(r, rec);
-            if mat_rec.scatter {
-                return mat_rec.attenuation
-                    * ray_color(mat_rec.scattered.unwrap(), world, depth - 1);
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let (flag, inline_value) = match arg.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => {
+                (flag.to_string(), Some(value.to_string()))
+            }
+            _ => (arg.clone(), None),
+        };
+        let mut value = || {
+            inline_value
+                .clone()
+                .or_else(|| args.next())
+                .ok_or_else(|| format!("{} needs a value", flag))
+        };
+
+        match flag.as_str() {
+            "-h" | "--help" => options.help = true,
+            "-s" | "--scene" => {
+                let name = value()?;
+                if !BUILTIN_SCENES.contains(&name.as_str()) {
+                    return Err(format!(
+                        "unknown scene '{}', expected one of: {}",
+                        name,
+                        BUILTIN_SCENES.join(", ")
+                    ));
+                }
+                options.scene_name = Some(name);
+            }
+            "-o" | "--output" => options.output = Some(value()?),
+            "-f" | "--format" => {
+                let format = value()?;
+                options.format = Some(ImageFormat::from_name(&format).ok_or_else(|| {
+                    format!(
+                        "unsupported format '{}', expected one of: png, ppm, ppm-plain, exr, hdr",
+                        format
+                    )
+                })?);
+            }
+            "--exposure" => {
+                let exposure = value()?;
+                options.tone_map.exposure = match exposure.parse::<f64>() {
+                    Ok(ev) if ev.is_finite() => ev,
+                    _ => return Err(format!("--exposure expects a number, got '{}'", exposure)),
+                };
+            }
+            "--tonemap" => {
+                let name = value()?;
+                options.tone_map.operator = ToneMapOperator::from_name(&name).ok_or_else(|| {
+                    format!(
+                        "unknown tone mapping '{}', expected one of: clamp, reinhard, reinhard-extended, aces",
+                        name
+                    )
+                })?;
+            }
+            "--white-point" => {
+                let white = value()?;
+                options.tone_map.white_point = match white.parse::<f64>() {
+                    Ok(w) if w > 0.0 && w.is_finite() => w,
+                    _ => {
+                        return Err(format!(
+                            "--white-point expects a positive number, got '{}'",
+                            white
+                        ))
+                    }
+                };
+            }
+            "--transfer" => {
+                let name = value()?;
+                options.tone_map.transfer =
+                    TransferFunction::from_name(&name).ok_or_else(|| {
+                        format!(
+                            "unknown transfer function '{}', expected one of: gamma2, srgb",
+                            name
+                        )
+                    })?;
+            }
+            "-W" | "--width" => options.width = Some(parse_count(&flag, &value()?, 2)?),
+            "-H" | "--height" => options.height = Some(parse_count(&flag, &value()?, 2)?),
+            "-n" | "--samples" => options.samples = Some(parse_count(&flag, &value()?, 1)?),
+            "-d" | "--max-depth" => options.max_depth = Some(parse_count(&flag, &value()?, 1)?),
+            "-i" | "--integrator" => {
+                let name = value()?;
+                if !INTEGRATORS.contains(&name.as_str()) {
+                    return Err(format!(
+                        "unknown integrator '{}', expected one of: {}",
+                        name,
+                        INTEGRATORS.join(", ")
+                    ));
+                }
+                options.integrator = Some(name);
+            }
+            "-j" | "--threads" => options.threads = Some(parse_count(&flag, &value()?, 1)?),
+            "--seed" => {
+                let seed = value()?;
+                options.seed = Some(seed.parse().map_err(|_| {
+                    format!("--seed expects a non-negative integer, got '{}'", seed)
+                })?);
+            }
+            "--crop" => options.crop = Some(parse_crop(&value()?)?),
+            _ if flag.starts_with('-') && flag.len() > 1 => {
+                return Err(format!("unknown option '{}'", flag));
+            }
+            _ => {
+                if options.scene_file.is_some() {
+                    return Err(format!("unexpected argument '{}'", arg));
+                }
+                options.scene_file = Some(arg);
             }
         }
-        return Color::new(0.0, 0.0, 0.0);
     }
-    let unit_direction = r.direction().unit_vector();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-    ((1.0 - t) * Color::new(1.0, 1.0, 1.0)) + (t * Color::new(0.5, 0.7, 1.0))
+
+    if let (None, Some(output)) = (options.format, &options.output) {
+        options.format = Some(ImageFormat::from_path(output).ok_or_else(|| {
+            format!(
+                "can't tell the image format of '{}'; use a .png, .ppm, .pnm, .exr or .hdr extension or --format",
+                output
+            )
+        })?);
+    }
+
+    if options.scene_file.is_some() && options.scene_name.is_some() {
+        return Err(String::from(
+            "give either a scene file or --scene, not both",
+        ));
+    }
+
+    Ok(options)
 }
 
-fn random_scene() -> HittableList<Sphere> {
-    let ground_material = Rc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+fn parse_count<T: FromStr + PartialOrd + Display>(
+    flag: &str,
+    value: &str,
+    min: T,
+) -> Result<T, String> {
+    match value.parse::<T>() {
+        Ok(n) if n >= min => Ok(n),
+        _ => Err(format!(
+            "{} expects an integer of at least {}, got '{}'",
+            flag, min, value
+        )),
+    }
+}
 
-    let mut world = HittableList {
-        objects: vec![Rc::new(Sphere::new(
-            Vec3::new(0.0, -1000.0, 0.0),
-            1000.0,
-            ground_material,
-        ))],
-    };
+fn parse_crop(value: &str) -> Result<Crop, String> {
+    let parts: Vec<usize> = value
+        .split(',')
+        .map(|part| part.trim().parse())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("--crop expects X0,Y0,X1,Y1, got '{}'", value))?;
+    match parts[..] {
+        [x0, y0, x1, y1] if x0 < x1 && y0 < y1 => Ok(Crop { x0, y0, x1, y1 }),
+        [_, _, _, _] => Err(format!("--crop window '{}' is empty", value)),
+        _ => Err(format!("--crop expects X0,Y0,X1,Y1, got '{}'", value)),
+    }
+}
 
-    for a in -11..11 {
-        for b in -11..11 {
-            let choose_mat = ray_tracer::random();
-            let center = Point3::new(
-                a as f64 + 0.9 * ray_tracer::random(),
-                0.2,
-                b as f64 + 0.9 * ray_tracer::random(),
-            );
-
-            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
-                let sphere_material: Rc<dyn Material>;
-                if choose_mat < 0.8 {
-                    // diffuse
-                    let albedo = vec3_random() * vec3_random();
-                    sphere_material = Rc::new(Lambertian::new(albedo));
-                    // world
-                    //     .objects
-                    //     .push(Rc::new(Sphere::new(center, 0.2, sphere_material)));
-                } else if choose_mat < 0.95 {
-                    // metal
-                    let albedo = vec3_random_in_range(0.5, 1.0);
-                    let fuzz = random_in_range(0.0, 0.5);
-                    sphere_material = Rc::new(Metal::new(albedo, fuzz));
-                    // world
-                    //     .objects
-                    //     .push(Rc::new(Sphere::new(center, 0.2, sphere_material)));
-                } else {
-                    // glass
-                    sphere_material = Rc::new(Dielectric::new(1.5));
-                    // world
-                    //     .objects
-                    //     .push(Rc::new(Sphere::new(center, 0.2, sphere_material)));
-                }
-                world
-                    .objects
-                    .push(Rc::new(Sphere::new(center, 0.2, sphere_material)));
-            }
+/// Applies the command-line overrides to a loaded scene.
+fn apply_options(scene: &mut Scene, options: &Options) -> Result<(), String> {
+    let settings = &mut scene.settings;
+    let aspect_ratio = settings.aspect_ratio();
+    match (options.width, options.height) {
+        (Some(width), Some(height)) => {
+            settings.image_width = width;
+            settings.image_height = height;
+            scene.camera = scene.camera.with_aspect_ratio(settings.aspect_ratio());
+        }
+        (Some(width), None) => {
+            settings.image_width = width;
+            settings.image_height = ((width as f64 / aspect_ratio) as usize).max(2);
         }
+        (None, Some(height)) => {
+            settings.image_height = height;
+            settings.image_width = ((height as f64 * aspect_ratio) as usize).max(2);
+        }
+        (None, None) => {}
+    }
+
+    if let Some(samples) = options.samples {
+        settings.samples_per_pixel = samples;
+    }
+    if let Some(max_depth) = options.max_depth {
+        settings.max_depth = max_depth;
+    }
+    if let Some(threads) = options.threads {
+        settings.threads = threads;
+    }
+    settings.seed = options.seed;
+
+    if let Some(crop) = options.crop {
+        if crop.x1 > settings.image_width || crop.y1 > settings.image_height {
+            return Err(format!(
+                "--crop window {},{},{},{} lies outside the {}x{} image",
+                crop.x0, crop.y0, crop.x1, crop.y1, settings.image_width, settings.image_height
+            ));
+        }
+        settings.crop = Some(crop);
     }
 
-    let material1 = Rc::new(Dielectric::new(1.5));
-    world.objects.push(Rc::new(Sphere::new(
-        Point3::new(0.0, 1.0, 0.0),
-        1.0,
-        material1,
-    )));
-
-    let material2 = Rc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
-    world.objects.push(Rc::new(Sphere::new(
-        Point3::new(-4.0, 1.0, 0.0),
-        1.0,
-        material2,
-    )));
-
-    let material3 = Rc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
-    world.objects.push(Rc::new(Sphere::new(
-        Point3::new(4.0, 1.0, 0.0),
-        1.0,
-        material3,
-    )));
-
-    world
+    Ok(())
 }
 
-fn main() {
-    // Image
-    const ASPECT_RATIO: f64 = 3.0 / 2.0;
-    const IMAGE_WIDTH: i32 = 1200;
-    const IMAGE_HEIGHT: i32 = (IMAGE_WIDTH as f64 / ASPECT_RATIO) as i32;
-    const SAMPLES_PER_PIXEL: usize = 100; // TODO: Is this the optimal sample size?
-    const MAX_DEPTH: i32 = 50;
-
-    // World
-    let world = random_scene();
-
-    // Camera
-    let look_from = Point3::new(13.0, 2.0, 3.0);
-    let look_at = Point3::new(0.0, 0.0, 0.0);
-    let camera = Camera::new(
-        look_from,
-        look_at,
-        Vec3::new(0.0, 1.0, 0.0),
-        20.0,
-        ASPECT_RATIO,
-        0.1,
-        10.0,
-    );
+fn run(options: &Options) -> Result<(), String> {
+    // Seed before building the scene so random scenes are reproducible too.
+    seed_thread_rng(options.seed);
 
-    // Render
-    println!("P3");
-    println!("{} {}", IMAGE_WIDTH, IMAGE_HEIGHT);
-    println!("255");
+    let mut scene = match (&options.scene_file, &options.scene_name) {
+        (Some(path), _) => load_scene(path).map_err(|e| e.to_string())?,
+        (None, name) => builtin_scene(name.as_deref().unwrap_or("random")).unwrap(),
+    };
+    apply_options(&mut scene, options)?;
 
-    for j in (0..IMAGE_HEIGHT).rev() {
-        eprintln!("Scanlines remaining: {}", j);
-        for i in 0..IMAGE_WIDTH {
+    let integrator = integrator_from_name(options.integrator.as_deref().unwrap_or("path")).unwrap();
+    let settings = &scene.settings;
+    let image_width = settings.image_width;
+    let image_height = settings.image_height;
+
+    // Render
+    let film = render(
+        settings,
+        |i, j| {
             let mut pixel_color = Color::new(0.0, 0.0, 0.0);
-            for _s in 0..SAMPLES_PER_PIXEL {
-                let u = (i as f64 + ray_tracer::random()) / (IMAGE_WIDTH - 1) as f64;
-                let v = (j as f64 + ray_tracer::random()) / (IMAGE_HEIGHT - 1) as f64;
-                let r = camera.get_ray(u, v);
-                pixel_color += ray_color(r, &world, MAX_DEPTH);
+            for _s in 0..settings.samples_per_pixel {
+                let u = (i as f64 + ray_tracer::random()) / (image_width - 1) as f64;
+                let v = (j as f64 + ray_tracer::random()) / (image_height - 1) as f64;
+                let r = scene.camera.get_ray(u, v);
+                pixel_color += integrator.radiance(r, &scene);
             }
-            println!("{}", pixel_color.to_color_string(SAMPLES_PER_PIXEL));
-        }
-    }
+            pixel_color
+        },
+        |remaining| eprintln!("Scanlines remaining: {}", remaining),
+    );
+
+    // Output
+    let format = options.format.unwrap_or(ImageFormat::PlainPpm);
+    let write_error = |e: io::Error| format!("failed to write image: {}", e);
+    let mut out: Box<dyn Write> = match &options.output {
+        Some(path) => Box::new(BufWriter::new(
+            File::create(path).map_err(|e| format!("{}: {}", path, e))?,
+        )),
+        None => Box::new(BufWriter::new(io::stdout().lock())),
+    };
+    film.encode(&mut out, format, &options.tone_map)
+        .map_err(write_error)?;
+    out.flush().map_err(write_error)?;
 
     eprintln!("Done.");
+    Ok(())
+}
+
+fn main() {
+    let options = parse_args(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("error: {}\n\n{}", e, USAGE);
+        process::exit(2);
+    });
+    if options.help {
+        print!("{}", USAGE);
+        return;
+    }
+
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
+
+#[cfg(test)]
+fn args(line: &str) -> Vec<String> {
+    line.split_whitespace().map(String::from).collect()
+}
+
+#[test]
+fn can_parse_args() {
+    let options = parse_args(args(
+        "scene.toml -o out.png --width=320 -n 16 --exposure -1.5 --tonemap aces --seed 3 -i nee --crop 0,10,100,90",
+    ))
+    .unwrap();
+    assert_eq!(options.scene_file.as_deref(), Some("scene.toml"));
+    assert_eq!(options.output.as_deref(), Some("out.png"));
+    assert_eq!(options.format, Some(ImageFormat::Png));
+    assert_eq!(options.width, Some(320));
+    assert_eq!(options.samples, Some(16));
+    assert_eq!(options.tone_map.exposure, -1.5);
+    assert_eq!(options.tone_map.operator, ToneMapOperator::Aces);
+    assert_eq!(options.seed, Some(3));
+    assert_eq!(options.integrator.as_deref(), Some("nee"));
+    assert_eq!(
+        options.crop,
+        Some(Crop {
+            x0: 0,
+            y0: 10,
+            x1: 100,
+            y1: 90
+        })
+    );
+}
+
+#[test]
+fn rejects_bad_args() {
+    assert!(parse_args(args("--samples 0")).is_err());
+    assert!(parse_args(args("--max-depth 4294967296")).is_err());
+    assert!(parse_args(args("--width ten")).is_err());
+    assert!(parse_args(args("--crop 5,5,5,10")).is_err());
+    assert!(parse_args(args("--crop 1,2,3")).is_err());
+    assert!(parse_args(args("--scene nope")).is_err());
+    assert!(parse_args(args("--threads")).is_err());
+    assert!(parse_args(args("--bogus")).is_err());
+    assert!(parse_args(args("--format gif")).is_err());
+    assert!(parse_args(args("--white-point 0")).is_err());
+    assert!(parse_args(args("--tonemap hable")).is_err());
+    assert!(parse_args(args("--integrator bdpt")).is_err());
+    assert!(parse_args(args("-o image.gif")).is_err());
+    assert!(parse_args(args("a.toml --scene random")).is_err());
 }
@@ -0,0 +1,161 @@
+use std::sync::Arc;
+
+use crate::{random_in_range, Aabb, HitRecord, Hittable, Material, Point3, Ray, Vec3};
+
+/// Minimum thickness of a rectangle's bounding box along its normal.
+const BOX_PADDING: f64 = 1e-4;
+
+/// Coordinate plane an `AaRect` lies parallel to.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Plane {
+    Xy,
+    Xz,
+    Yz,
+}
+
+impl Plane {
+    /// Indices of the two in-plane axes and of the normal axis.
+    fn axes(&self) -> (usize, usize, usize) {
+        match self {
+            Plane::Xy => (0, 1, 2),
+            Plane::Xz => (0, 2, 1),
+            Plane::Yz => (1, 2, 0),
+        }
+    }
+}
+
+/// An axis-aligned rectangle spanning `a0..a1` and `b0..b1` along the two
+/// in-plane axes, at `k` along the normal axis.
+///
+/// Cheaper to intersect than a `Quad`; the outward normal points along the
+/// positive normal axis.
+pub struct AaRect {
+    pub plane: Plane,
+    pub a0: f64,
+    pub a1: f64,
+    pub b0: f64,
+    pub b1: f64,
+    pub k: f64,
+    pub material: Arc<dyn Material>,
+}
+
+impl AaRect {
+    /// # Panics
+    ///
+    /// If `a0 >= a1` or `b0 >= b1`, so the rectangle has no area.
+    pub fn new(
+        plane: Plane,
+        a0: f64,
+        a1: f64,
+        b0: f64,
+        b1: f64,
+        k: f64,
+        material: Arc<dyn Material>,
+    ) -> AaRect {
+        assert!(
+            a0 < a1 && b0 < b1,
+            "rectangle spans {}..{} by {}..{}, which has no area",
+            a0,
+            a1,
+            b0,
+            b1
+        );
+        AaRect {
+            plane,
+            a0,
+            a1,
+            b0,
+            b1,
+            k,
+            material,
+        }
+    }
+
+    fn point(&self, a: f64, b: f64, k: f64) -> Point3 {
+        let mut p = [0.0; 3];
+        let (ia, ib, ik) = self.plane.axes();
+        p[ia] = a;
+        p[ib] = b;
+        p[ik] = k;
+        Point3::new(p[0], p[1], p[2])
+    }
+}
+
+impl Hittable for AaRect {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let (ia, ib, ik) = self.plane.axes();
+        let t = (self.k - ray.origin()[ik]) / ray.direction()[ik];
+        if !(t_min..=t_max).contains(&t) {
+            return false;
+        }
+
+        let p = ray.at(t);
+        let (a, b) = (p[ia], p[ib]);
+        if a < self.a0 || a > self.a1 || b < self.b0 || b > self.b1 {
+            return false;
+        }
+
+        rec.t = Some(t);
+        rec.p = Some(p);
+        rec.set_face_normal(&ray, self.point(0.0, 0.0, 1.0));
+        rec.material = Some(self.material.clone());
+        rec.barycentric = None;
+        rec.uv = Some((
+            (a - self.a0) / (self.a1 - self.a0),
+            (b - self.b0) / (self.b1 - self.b0),
+        ));
+
+        true
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            self.point(self.a0, self.b0, self.k - BOX_PADDING),
+            self.point(self.a1, self.b1, self.k + BOX_PADDING),
+        ))
+    }
+
+    /// Uniform over the rectangle's area, converted to solid angle.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(Ray::new(origin, direction), 0.001, f64::INFINITY, &mut rec) {
+            return 0.0;
+        }
+
+        let (_, _, ik) = self.plane.axes();
+        let t = rec.t.unwrap();
+        let distance_squared = t * t * direction.length_squared();
+        let cosine = (direction[ik] / direction.length()).abs();
+        let area = (self.a1 - self.a0) * (self.b1 - self.b0);
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let a = random_in_range(self.a0, self.a1);
+        let b = random_in_range(self.b0, self.b1);
+        self.point(a, b, self.k) - origin
+    }
+}
+
+#[test]
+fn can_hit_aa_rect() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let rect = AaRect::new(Plane::Xz, 0.0, 2.0, -1.0, 1.0, 3.0, material);
+    let mut rec = HitRecord::new();
+    let ray = Ray::new(Point3::new(0.5, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0));
+    assert!(rect.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(3.0));
+    assert_eq!(rec.uv, Some((0.25, 0.5)));
+    assert_eq!(rec.normal, Some(Vec3::new(0.0, -1.0, 0.0)));
+    assert_eq!(rec.front_face, Some(false));
+
+    let bbox = rect.bounding_box().unwrap();
+    assert_eq!(bbox.min(), Point3::new(0.0, 3.0 - BOX_PADDING, -1.0));
+}
+
+#[test]
+#[should_panic(expected = "which has no area")]
+fn rejects_reversed_rect_bounds() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    AaRect::new(Plane::Xz, 1.0, 0.0, -1.0, 1.0, 3.0, material);
+}
@@ -0,0 +1,112 @@
+use crate::{Point3, Ray};
+
+/// Axis-aligned bounding box.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Aabb {
+    minimum: Point3,
+    maximum: Point3,
+}
+
+impl Aabb {
+    pub fn new(a: Point3, b: Point3) -> Aabb {
+        Aabb {
+            minimum: Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z())),
+            maximum: Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z())),
+        }
+    }
+
+    pub fn min(&self) -> Point3 {
+        self.minimum
+    }
+
+    pub fn max(&self) -> Point3 {
+        self.maximum
+    }
+
+    pub fn centroid(&self) -> Point3 {
+        0.5 * (self.minimum + self.maximum)
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.maximum - self.minimum;
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    /// Returns the index of the axis along which the box is widest.
+    pub fn longest_axis(&self) -> usize {
+        let d = self.maximum - self.minimum;
+        if d.x() > d.y() && d.x() > d.z() {
+            0
+        } else if d.y() > d.z() {
+            1
+        } else {
+            2
+        }
+    }
+
+    /// Slab test against the ray within the interval [t_min, t_max].
+    pub fn hit(&self, ray: Ray, t_min: f64, t_max: f64) -> bool {
+        self.intersect(ray, t_min, t_max).is_some()
+    }
+
+    /// Returns the part of [t_min, t_max] during which the ray is inside
+    /// the box.
+    pub fn intersect(&self, ray: Ray, mut t_min: f64, mut t_max: f64) -> Option<(f64, f64)> {
+        let origin = ray.origin();
+        let direction = ray.direction();
+        for a in 0..3 {
+            let inv_d = 1.0 / direction[a];
+            let mut t0 = (self.minimum[a] - origin[a]) * inv_d;
+            let mut t1 = (self.maximum[a] - origin[a]) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            t_min = if t0 > t_min { t0 } else { t_min };
+            t_max = if t1 < t_max { t1 } else { t_max };
+            if t_max <= t_min {
+                return None;
+            }
+        }
+        Some((t_min, t_max))
+    }
+}
+
+/// Returns the smallest box enclosing both boxes.
+pub fn surrounding_box(box0: &Aabb, box1: &Aabb) -> Aabb {
+    let small = Point3::new(
+        box0.min().x().min(box1.min().x()),
+        box0.min().y().min(box1.min().y()),
+        box0.min().z().min(box1.min().z()),
+    );
+    let big = Point3::new(
+        box0.max().x().max(box1.max().x()),
+        box0.max().y().max(box1.max().y()),
+        box0.max().z().max(box1.max().z()),
+    );
+    Aabb {
+        minimum: small,
+        maximum: big,
+    }
+}
+
+#[test]
+fn can_hit_aabb() {
+    let bbox = Aabb::new(Point3::new(-1.0, -1.0, -1.0), Point3::new(1.0, 1.0, 1.0));
+    let toward = Ray::new(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, 1.0));
+    let away = Ray::new(Point3::new(0.0, 0.0, -5.0), Point3::new(0.0, 0.0, -1.0));
+    let beside = Ray::new(Point3::new(2.0, 0.0, -5.0), Point3::new(0.0, 0.0, 1.0));
+    assert!(bbox.hit(toward, 0.001, f64::INFINITY));
+    assert!(!bbox.hit(away, 0.001, f64::INFINITY));
+    assert!(!bbox.hit(beside, 0.001, f64::INFINITY));
+    assert!(!bbox.hit(toward, 0.001, 3.0));
+}
+
+#[test]
+fn can_surround_aabbs() {
+    let a = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0));
+    let b = Aabb::new(Point3::new(-2.0, 0.5, 0.5), Point3::new(0.5, 3.0, 0.5));
+    let res = surrounding_box(&a, &b);
+    assert_eq!(res.min(), Point3::new(-2.0, 0.0, 0.0));
+    assert_eq!(res.max(), Point3::new(1.0, 3.0, 1.0));
+    assert_eq!(res.longest_axis(), 1);
+}
@@ -0,0 +1,23 @@
+use crate::{Color, Ray};
+
+/// Radiance arriving along rays that escape the scene.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Background {
+    /// White-to-blue vertical gradient.
+    Sky,
+    /// A constant color, e.g. black for interiors lit only by their lights.
+    Solid(Color),
+}
+
+impl Background {
+    pub fn color(&self, ray: &Ray) -> Color {
+        match self {
+            Background::Sky => {
+                let unit_direction = ray.direction().unit_vector();
+                let t = 0.5 * (unit_direction.y() + 1.0);
+                ((1.0 - t) * Color::new(1.0, 1.0, 1.0)) + (t * Color::new(0.5, 0.7, 1.0))
+            }
+            Background::Solid(color) => *color,
+        }
+    }
+}
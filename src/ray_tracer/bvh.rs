@@ -0,0 +1,281 @@
+use std::sync::Arc;
+
+use crate::{aabb::surrounding_box, Aabb, HitRecord, Hittable, HittableList, Ray};
+
+/// Strategy used to partition objects when building a `BvhNode`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum SplitHeuristic {
+    /// Split the widest axis at the midpoint of the object centroids.
+    Midpoint,
+    /// Binned surface area heuristic.
+    Sah,
+}
+
+const SAH_BUCKETS: usize = 12;
+
+/// Bounding volume hierarchy over a set of hittables.
+pub struct BvhNode {
+    left: Arc<dyn Hittable>,
+    right: Option<Arc<dyn Hittable>>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    /// Builds a tree over `objects`.
+    ///
+    /// Panics if `objects` is empty or any object has no bounding box.
+    pub fn new(objects: Vec<Arc<dyn Hittable>>, split: SplitHeuristic) -> BvhNode {
+        assert!(
+            !objects.is_empty(),
+            "cannot build a BvhNode without objects"
+        );
+        let mut primitives: Vec<(Arc<dyn Hittable>, Aabb)> = objects
+            .into_iter()
+            .map(|obj| {
+                let bbox = obj
+                    .bounding_box()
+                    .expect("no bounding box in BvhNode constructor");
+                (obj, bbox)
+            })
+            .collect();
+        Self::build(&mut primitives, split)
+    }
+
+    pub fn from_list<T: Hittable + 'static>(
+        list: &HittableList<T>,
+        split: SplitHeuristic,
+    ) -> BvhNode {
+        let objects = list
+            .objects
+            .iter()
+            .map(|obj| obj.clone() as Arc<dyn Hittable>)
+            .collect();
+        Self::new(objects, split)
+    }
+
+    fn build(primitives: &mut [(Arc<dyn Hittable>, Aabb)], split: SplitHeuristic) -> BvhNode {
+        let bbox = primitives[1..]
+            .iter()
+            .fold(primitives[0].1, |acc, (_, b)| surrounding_box(&acc, b));
+
+        match primitives.len() {
+            1 => {
+                return BvhNode {
+                    left: primitives[0].0.clone(),
+                    right: None,
+                    bbox,
+                }
+            }
+            2 => {
+                return BvhNode {
+                    left: primitives[0].0.clone(),
+                    right: Some(primitives[1].0.clone()),
+                    bbox,
+                }
+            }
+            _ => {}
+        }
+
+        let centroid_bounds = primitives[1..].iter().fold(
+            Aabb::new(primitives[0].1.centroid(), primitives[0].1.centroid()),
+            |acc, (_, b)| surrounding_box(&acc, &Aabb::new(b.centroid(), b.centroid())),
+        );
+        let axis = centroid_bounds.longest_axis();
+
+        let mid = match split {
+            SplitHeuristic::Midpoint => {
+                Self::partition_midpoint(primitives, &centroid_bounds, axis)
+            }
+            SplitHeuristic::Sah => Self::partition_sah(primitives, &centroid_bounds, axis),
+        };
+
+        // Fall back to an even split if the heuristic could not separate the objects.
+        let mid = match mid {
+            Some(mid) if mid > 0 && mid < primitives.len() => mid,
+            _ => {
+                primitives.sort_by(|a, b| a.1.centroid()[axis].total_cmp(&b.1.centroid()[axis]));
+                primitives.len() / 2
+            }
+        };
+
+        let (left, right) = primitives.split_at_mut(mid);
+        BvhNode {
+            left: Arc::new(Self::build(left, split)),
+            right: Some(Arc::new(Self::build(right, split))),
+            bbox,
+        }
+    }
+
+    fn partition_midpoint(
+        primitives: &mut [(Arc<dyn Hittable>, Aabb)],
+        centroid_bounds: &Aabb,
+        axis: usize,
+    ) -> Option<usize> {
+        let pivot = centroid_bounds.centroid()[axis];
+        Some(partition(primitives, |(_, b)| b.centroid()[axis] < pivot))
+    }
+
+    fn partition_sah(
+        primitives: &mut [(Arc<dyn Hittable>, Aabb)],
+        centroid_bounds: &Aabb,
+        axis: usize,
+    ) -> Option<usize> {
+        let lo = centroid_bounds.min()[axis];
+        let extent = centroid_bounds.max()[axis] - lo;
+        if extent <= 0.0 {
+            return None;
+        }
+
+        let bucket_of = |b: &Aabb| {
+            let i = (SAH_BUCKETS as f64 * (b.centroid()[axis] - lo) / extent) as usize;
+            i.min(SAH_BUCKETS - 1)
+        };
+
+        let mut counts = [0usize; SAH_BUCKETS];
+        let mut bounds: [Option<Aabb>; SAH_BUCKETS] = [None; SAH_BUCKETS];
+        for (_, b) in primitives.iter() {
+            let i = bucket_of(b);
+            counts[i] += 1;
+            bounds[i] = Some(match bounds[i] {
+                Some(acc) => surrounding_box(&acc, b),
+                None => *b,
+            });
+        }
+
+        // Cost of splitting after each bucket, up to a constant traversal term.
+        let merge = |acc: Option<Aabb>, b: &Option<Aabb>| match (acc, b) {
+            (Some(a), Some(b)) => Some(surrounding_box(&a, b)),
+            (a, b) => a.or(*b),
+        };
+        let mut best: Option<(usize, f64)> = None;
+        for split in 1..SAH_BUCKETS {
+            let (count0, count1): (usize, usize) =
+                (counts[..split].iter().sum(), counts[split..].iter().sum());
+            if count0 == 0 || count1 == 0 {
+                continue;
+            }
+            let area0 = bounds[..split]
+                .iter()
+                .fold(None, merge)
+                .unwrap()
+                .surface_area();
+            let area1 = bounds[split..]
+                .iter()
+                .fold(None, merge)
+                .unwrap()
+                .surface_area();
+            let cost = count0 as f64 * area0 + count1 as f64 * area1;
+            if best.is_none_or(|(_, c)| cost < c) {
+                best = Some((split, cost));
+            }
+        }
+
+        let (split, _) = best?;
+        Some(partition(primitives, |(_, b)| bucket_of(b) < split))
+    }
+}
+
+/// Reorders `items` so those matching `pred` come first, returning how many matched.
+fn partition<T>(items: &mut [T], pred: impl Fn(&T) -> bool) -> usize {
+    let mut first = 0;
+    for i in 0..items.len() {
+        if pred(&items[i]) {
+            items.swap(first, i);
+            first += 1;
+        }
+    }
+    first
+}
+
+impl Hittable for BvhNode {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return false;
+        }
+
+        let hit_left = self.left.hit(ray, t_min, t_max, rec);
+        let hit_right = match &self.right {
+            Some(right) => {
+                let closest = if hit_left { rec.t().unwrap() } else { t_max };
+                right.hit(ray, t_min, closest, rec)
+            }
+            None => false,
+        };
+
+        hit_left || hit_right
+    }
+
+    fn hit_surface(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return false;
+        }
+
+        let hit_left = self.left.hit_surface(ray, t_min, t_max, rec);
+        let hit_right = match &self.right {
+            Some(right) => {
+                let closest = if hit_left { rec.t().unwrap() } else { t_max };
+                right.hit_surface(ray, t_min, closest, rec)
+            }
+            None => false,
+        };
+
+        hit_left || hit_right
+    }
+
+    fn transmittance(&self, ray: Ray, t_min: f64, t_max: f64) -> f64 {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return 1.0;
+        }
+
+        let right = self
+            .right
+            .as_ref()
+            .map_or(1.0, |right| right.transmittance(ray, t_min, t_max));
+        self.left.transmittance(ray, t_min, t_max) * right
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
+
+#[cfg(test)]
+fn random_spheres(count: usize) -> Vec<Arc<dyn Hittable>> {
+    use crate::{random_in_range, vec3_random_in_range, Color, Lambertian, Sphere};
+
+    let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    (0..count)
+        .map(|_| {
+            Arc::new(Sphere::new(
+                vec3_random_in_range(-10.0, 10.0),
+                random_in_range(0.1, 1.0),
+                material.clone(),
+            )) as Arc<dyn Hittable>
+        })
+        .collect()
+}
+
+#[test]
+fn bvh_matches_linear_search() {
+    use crate::{vec3_random_in_range, Point3};
+
+    let objects = random_spheres(200);
+    let list = HittableList {
+        objects: objects.clone(),
+    };
+    for split in [SplitHeuristic::Midpoint, SplitHeuristic::Sah] {
+        let bvh = BvhNode::new(objects.clone(), split);
+        for _ in 0..500 {
+            let ray = Ray::new(
+                Point3::new(0.0, 0.0, -30.0),
+                vec3_random_in_range(-1.0, 1.0),
+            );
+            let mut list_rec = HitRecord::new();
+            let mut bvh_rec = HitRecord::new();
+            let list_hit = list.hit(ray, 0.001, f64::INFINITY, &mut list_rec);
+            let bvh_hit = bvh.hit(ray, 0.001, f64::INFINITY, &mut bvh_rec);
+            assert_eq!(list_hit, bvh_hit);
+            assert_eq!(list_rec.t(), bvh_rec.t());
+        }
+    }
+}
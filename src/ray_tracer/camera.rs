@@ -1,4 +1,5 @@
 use crate::{
+    random_in_range,
     vec3::{cross, random_in_unit_disk},
     Point3, Ray, Vec3,
 };
@@ -11,6 +12,13 @@ pub struct Camera {
     lens_radius: f64,
     u: Vec3,
     v: Vec3,
+    look_at: Point3,
+    vup: Vec3,
+    vert_field_of_view: f64,
+    focus_distance: f64,
+    /// Shutter open and close times.
+    time0: f64,
+    time1: f64,
 }
 
 impl Camera {
@@ -47,16 +55,54 @@ impl Camera {
             lens_radius,
             u,
             v,
+            look_at,
+            vup,
+            vert_field_of_view,
+            focus_distance,
+            time0: 0.0,
+            time1: 0.0,
         }
     }
 
+    /// Returns the same camera with its shutter open from `open` to `close`,
+    /// firing each ray at a random time in between.
+    pub fn with_shutter(self, open: f64, close: f64) -> Camera {
+        Camera {
+            time0: open,
+            time1: close,
+            ..self
+        }
+    }
+
+    /// Returns the same camera with a different image aspect ratio.
+    pub fn with_aspect_ratio(&self, aspect_ratio: f64) -> Camera {
+        Camera::new(
+            self.origin,
+            self.look_at,
+            self.vup,
+            self.vert_field_of_view,
+            aspect_ratio,
+            2.0 * self.lens_radius,
+            self.focus_distance,
+        )
+        .with_shutter(self.time0, self.time1)
+    }
+
     pub fn get_ray(&self, s: f64, t: f64) -> Ray {
         let rd = self.lens_radius * random_in_unit_disk();
         let offset = self.u * rd.x() + self.v * rd.y();
 
-        Ray::new(
+        // An instantaneous shutter leaves the random sequence of still scenes unchanged.
+        let time = if self.time1 > self.time0 {
+            random_in_range(self.time0, self.time1)
+        } else {
+            self.time0
+        };
+
+        Ray::with_time(
             self.origin + offset,
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
+            time,
         )
     }
 
@@ -0,0 +1,111 @@
+use crate::{
+    microfacet::{fresnel_conductor, reflect},
+    Color, Ggx, HitRecord, Material, MaterialRecord, MicrofacetPdf, Onb, Pdf, Ray, Vec3,
+};
+
+/// Names accepted by `Conductor::preset`.
+pub const CONDUCTOR_PRESETS: &[&str] =
+    &["aluminium", "chromium", "copper", "gold", "iron", "silver"];
+
+/// A metal described by its complex index of refraction `eta + i k`, given
+/// for red, green and blue, with the exact conductor Fresnel equations.
+///
+/// Smooth unless given a roughness, in which case reflections come from a
+/// GGX microfacet distribution as in `RoughConductor`.
+pub struct Conductor {
+    pub eta: Color,
+    pub k: Color,
+    /// `None` for a perfect mirror.
+    pub distribution: Option<Ggx>,
+}
+
+impl Conductor {
+    /// # Panics
+    ///
+    /// If any channel of `eta` is not positive or of `k` is negative, or
+    /// either is not finite.
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Conductor {
+        assert!(
+            (0..3).all(|i| eta[i] > 0.0 && k[i] >= 0.0 && eta[i].is_finite() && k[i].is_finite()),
+            "conductor needs a positive eta and non-negative k, got {:?} and {:?}",
+            eta,
+            k
+        );
+        Conductor {
+            eta,
+            k,
+            distribution: (roughness > 0.0).then(|| Ggx::new(roughness)),
+        }
+    }
+
+    /// One of the metals in `CONDUCTOR_PRESETS`, with the standard RGB
+    /// indices used by Mitsuba: measured spectra integrated against the red,
+    /// green and blue primaries.
+    pub fn preset(name: &str, roughness: f64) -> Option<Conductor> {
+        let (eta, k) = match name {
+            "aluminium" => ((1.657, 0.880, 0.521), (9.224, 6.270, 4.837)),
+            "chromium" => ((4.360, 2.910, 1.650), (5.195, 4.225, 3.747)),
+            "copper" => ((0.200, 0.924, 1.102), (3.912, 2.452, 2.142)),
+            "gold" => ((0.143, 0.374, 1.442), (3.983, 2.385, 1.603)),
+            "iron" => ((2.950, 2.930, 2.650), (3.070, 2.930, 2.810)),
+            "silver" => ((0.155, 0.117, 0.138), (4.828, 3.122, 2.147)),
+            _ => return None,
+        };
+        Some(Conductor::new(
+            Color::new(eta.0, eta.1, eta.2),
+            Color::new(k.0, k.1, k.2),
+            roughness,
+        ))
+    }
+}
+
+impl Material for Conductor {
+    fn scatter(&self, ray: Ray, rec: HitRecord) -> MaterialRecord {
+        let uvw = Onb::from_w(rec.normal.unwrap());
+        let wo = uvw.to_local(-ray.direction().unit_vector());
+        if wo.z() <= 0.0 {
+            return MaterialRecord::absorbed();
+        }
+
+        let fresnel = |cosine| fresnel_conductor(cosine, self.eta, self.k);
+        let (wi, attenuation) = match self.distribution {
+            Some(ggx) => reflect(ggx, wo, fresnel),
+            None => (Vec3::new(-wo.x(), -wo.y(), wo.z()), fresnel(wo.z())),
+        };
+        MaterialRecord {
+            attenuation,
+            scattered: Some(Ray::with_time(rec.p.unwrap(), uvw.local(wi), ray.time())),
+            scatter: true,
+            pdf: self.distribution.map(|ggx| {
+                Box::new(MicrofacetPdf::new(
+                    ggx,
+                    rec.normal.unwrap(),
+                    ray.direction(),
+                    None,
+                )) as Box<dyn Pdf>
+            }),
+        }
+    }
+
+    fn bsdf_cos(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        _mat_rec: &MaterialRecord,
+        scattered: &Ray,
+    ) -> Color {
+        let Some(ggx) = self.distribution else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+        let uvw = Onb::from_w(rec.normal.unwrap());
+        let wo = uvw.to_local(-ray.direction().unit_vector());
+        let wi = uvw.to_local(scattered.direction().unit_vector());
+        ggx.reflection(wo, wi, |cosine| fresnel_conductor(cosine, self.eta, self.k))
+    }
+}
+
+#[test]
+#[should_panic(expected = "positive eta and non-negative k")]
+fn rejects_negative_extinction() {
+    Conductor::new(Color::new(0.2, 0.9, 1.1), Color::new(3.9, -2.4, 2.1), 0.0);
+}
@@ -0,0 +1,150 @@
+use std::sync::Arc;
+
+use crate::{random, Aabb, Color, HitRecord, Hittable, Isotropic, Material, Ray, Vec3};
+
+/// A volume of uniform density filling a closed boundary, such as fog or
+/// smoke.
+///
+/// Rays passing through are scattered after an exponentially distributed
+/// free-flight distance. The boundary must be convex, since only its first
+/// entry and exit are considered.
+pub struct ConstantMedium {
+    pub boundary: Arc<dyn Hittable>,
+    neg_inv_density: f64,
+    pub phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    /// Fills `boundary` with an isotropic medium of the given color.
+    pub fn new(boundary: Arc<dyn Hittable>, density: f64, color: Color) -> ConstantMedium {
+        ConstantMedium::with_phase_function(boundary, density, Arc::new(Isotropic::new(color)))
+    }
+
+    /// # Panics
+    ///
+    /// If `density` is not a positive number.
+    pub fn with_phase_function(
+        boundary: Arc<dyn Hittable>,
+        density: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> ConstantMedium {
+        assert!(
+            density > 0.0,
+            "medium density must be positive, got {}",
+            density
+        );
+        ConstantMedium {
+            boundary,
+            neg_inv_density: -1.0 / density,
+            phase_function,
+        }
+    }
+
+    /// Where the ray enters and leaves the boundary between `t_min` and
+    /// `t_max`, even if it starts inside.
+    fn span(&self, ray: Ray, t_min: f64, t_max: f64) -> Option<(f64, f64)> {
+        let mut enter = HitRecord::new();
+        if !self
+            .boundary
+            .hit(ray, f64::NEG_INFINITY, f64::INFINITY, &mut enter)
+        {
+            return None;
+        }
+        let mut exit = HitRecord::new();
+        if !self
+            .boundary
+            .hit(ray, enter.t.unwrap() + 0.0001, f64::INFINITY, &mut exit)
+        {
+            return None;
+        }
+
+        let t_enter = enter.t.unwrap().max(t_min).max(0.0);
+        let t_exit = exit.t.unwrap().min(t_max);
+        (t_enter < t_exit).then_some((t_enter, t_exit))
+    }
+}
+
+impl Hittable for ConstantMedium {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let Some((t_enter, t_exit)) = self.span(ray, t_min, t_max) else {
+            return false;
+        };
+
+        let ray_length = ray.direction().length();
+        let distance_inside = (t_exit - t_enter) * ray_length;
+        let hit_distance = self.neg_inv_density * random().ln();
+        if hit_distance > distance_inside {
+            return false;
+        }
+
+        let t = t_enter + hit_distance / ray_length;
+        rec.t = Some(t);
+        rec.p = Some(ray.at(t));
+        // Arbitrary, since the phase function ignores the surface.
+        rec.normal = Some(Vec3::new(1.0, 0.0, 0.0));
+        rec.front_face = Some(true);
+        rec.material = Some(self.phase_function.clone());
+        rec.barycentric = None;
+        rec.uv = None;
+
+        true
+    }
+
+    fn hit_surface(&self, _ray: Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord) -> bool {
+        false
+    }
+
+    /// Beer-Lambert attenuation over the distance inside the boundary.
+    fn transmittance(&self, ray: Ray, t_min: f64, t_max: f64) -> f64 {
+        match self.span(ray, t_min, t_max) {
+            Some((t_enter, t_exit)) => {
+                let distance_inside = (t_exit - t_enter) * ray.direction().length();
+                (distance_inside / self.neg_inv_density).exp()
+            }
+            None => 1.0,
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
+
+#[test]
+fn medium_scatters_at_expected_depth() {
+    use crate::{Lambertian, Point3, Sphere};
+
+    crate::seed_thread_rng(Some(5));
+    let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let boundary = Arc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 100.0, material));
+    let medium = ConstantMedium::new(boundary, 0.5, Color::new(1.0, 1.0, 1.0));
+
+    // Starting inside, the mean free path is 1 / density.
+    let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0));
+    let samples = 10_000;
+    let mut total = 0.0;
+    for _ in 0..samples {
+        let mut rec = HitRecord::new();
+        assert!(medium.hit(ray, 0.001, f64::INFINITY, &mut rec));
+        total += rec.p.unwrap().x();
+    }
+    crate::seed_thread_rng(None);
+    let mean = total / samples as f64;
+    assert!((mean - 2.0).abs() < 0.1, "{}", mean);
+
+    // Shadow rays see the medium only through its transmittance.
+    assert!(!medium.hit_surface(ray, 0.001, f64::INFINITY, &mut HitRecord::new()));
+    let transmittance = medium.transmittance(ray, 0.0, 1.0);
+    assert!((transmittance - f64::exp(-1.0)).abs() < 1e-9);
+}
+
+#[test]
+#[should_panic(expected = "density must be positive")]
+fn rejects_zero_density() {
+    let boundary = Arc::new(crate::Sphere::new(
+        crate::Point3::new(0.0, 0.0, 0.0),
+        1.0,
+        Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0))),
+    ));
+    ConstantMedium::new(boundary, 0.0, Color::new(1.0, 1.0, 1.0));
+}
@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use crate::{Aabb, HitRecord, Hittable, HittableList, Material, Point3, Quad, Ray, Vec3};
+
+/// An axis-aligned box made of six outward-facing quads.
+///
+/// Named to avoid clashing with `std::boxed::Box`.
+pub struct Cuboid {
+    sides: HittableList<Quad>,
+    bbox: Aabb,
+}
+
+impl Cuboid {
+    /// Builds the box with opposite corners `a` and `b`.
+    ///
+    /// # Panics
+    ///
+    /// If the corners share a coordinate, leaving the box flat.
+    pub fn new(a: Point3, b: Point3, material: Arc<dyn Material>) -> Cuboid {
+        let min = Point3::new(a.x().min(b.x()), a.y().min(b.y()), a.z().min(b.z()));
+        let max = Point3::new(a.x().max(b.x()), a.y().max(b.y()), a.z().max(b.z()));
+
+        let dx = Vec3::new(max.x() - min.x(), 0.0, 0.0);
+        let dy = Vec3::new(0.0, max.y() - min.y(), 0.0);
+        let dz = Vec3::new(0.0, 0.0, max.z() - min.z());
+
+        let side = |q: Point3, u: Vec3, v: Vec3| Arc::new(Quad::new(q, u, v, material.clone()));
+        let sides = HittableList {
+            objects: vec![
+                side(Point3::new(min.x(), min.y(), max.z()), dx, dy), // front
+                side(Point3::new(max.x(), min.y(), max.z()), -dz, dy), // right
+                side(Point3::new(max.x(), min.y(), min.z()), -dx, dy), // back
+                side(Point3::new(min.x(), min.y(), min.z()), dz, dy), // left
+                side(Point3::new(min.x(), max.y(), max.z()), dx, -dz), // top
+                side(Point3::new(min.x(), min.y(), min.z()), dx, dz), // bottom
+            ],
+        };
+
+        Cuboid {
+            sides,
+            bbox: Aabb::new(min, max),
+        }
+    }
+}
+
+impl Hittable for Cuboid {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.sides.hit(ray, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        self.sides.pdf_value(origin, direction)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        self.sides.random(origin)
+    }
+}
+
+#[test]
+fn cuboid_normals_face_outward() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let cuboid = Cuboid::new(
+        Point3::new(1.0, 1.0, 1.0),
+        Point3::new(-1.0, -1.0, -1.0),
+        material,
+    );
+    for axis in 0..3 {
+        for sign in [-1.0, 1.0] {
+            let mut d = [0.0; 3];
+            d[axis] = sign;
+            let direction = Vec3::new(d[0], d[1], d[2]);
+            let ray = Ray::new(-5.0 * direction, direction);
+            let mut rec = HitRecord::new();
+            assert!(cuboid.hit(ray, 0.001, f64::INFINITY, &mut rec));
+            assert_eq!(rec.t(), Some(4.0));
+            assert_eq!(rec.front_face, Some(true), "{:?}", direction);
+        }
+    }
+}
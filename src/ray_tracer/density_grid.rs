@@ -0,0 +1,209 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{Aabb, Point3};
+
+const GRID_MAGIC: &str = "DENSITY";
+
+/// Densities sampled on a regular voxel grid spanning `bounds`.
+///
+/// The file format is an ASCII header line `DENSITY nx ny nz` followed by
+/// `nx * ny * nz` little-endian 32-bit floats, with x varying fastest.
+pub struct DensityGrid {
+    nx: usize,
+    ny: usize,
+    nz: usize,
+    data: Vec<f32>,
+    bounds: Aabb,
+    max_density: f64,
+}
+
+impl DensityGrid {
+    /// Panics if `data` doesn't hold one value per voxel.
+    pub fn new(nx: usize, ny: usize, nz: usize, data: Vec<f32>, bounds: Aabb) -> DensityGrid {
+        assert_eq!(data.len(), nx * ny * nz, "grid data has the wrong size");
+        let max_density = data.iter().fold(0.0f64, |max, &d| max.max(d as f64));
+        DensityGrid {
+            nx,
+            ny,
+            nz,
+            data,
+            bounds,
+            max_density,
+        }
+    }
+
+    /// Samples a procedural density at the center of every voxel.
+    pub fn from_fn(
+        nx: usize,
+        ny: usize,
+        nz: usize,
+        bounds: Aabb,
+        density: impl Fn(Point3) -> f64,
+    ) -> DensityGrid {
+        let size = bounds.max() - bounds.min();
+        let mut data = Vec::with_capacity(nx * ny * nz);
+        for k in 0..nz {
+            for j in 0..ny {
+                for i in 0..nx {
+                    let p = bounds.min()
+                        + Point3::new(
+                            (i as f64 + 0.5) / nx as f64 * size.x(),
+                            (j as f64 + 0.5) / ny as f64 * size.y(),
+                            (k as f64 + 0.5) / nz as f64 * size.z(),
+                        );
+                    data.push(density(p).max(0.0) as f32);
+                }
+            }
+        }
+        DensityGrid::new(nx, ny, nz, data, bounds)
+    }
+
+    /// Loads a grid file, stretching it over `bounds`.
+    pub fn load(path: impl AsRef<Path>, bounds: Aabb) -> io::Result<DensityGrid> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)?;
+        DensityGrid::decode(&bytes, bounds).map_err(|message| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), message),
+            )
+        })
+    }
+
+    fn decode(bytes: &[u8], bounds: Aabb) -> Result<DensityGrid, String> {
+        let newline = bytes
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or("missing grid header")?;
+        let header = std::str::from_utf8(&bytes[..newline]).map_err(|_| "invalid grid header")?;
+        let mut fields = header.split_whitespace();
+        if fields.next() != Some(GRID_MAGIC) {
+            return Err(String::from("not a density grid"));
+        }
+        let mut dimension = || -> Result<usize, String> {
+            fields
+                .next()
+                .and_then(|field| field.parse().ok())
+                .filter(|&n| n > 0)
+                .ok_or_else(|| String::from("invalid grid dimensions"))
+        };
+        let (nx, ny, nz) = (dimension()?, dimension()?, dimension()?);
+        let expected = nx
+            .checked_mul(ny)
+            .and_then(|n| n.checked_mul(nz))
+            .and_then(|n| n.checked_mul(4))
+            .ok_or("invalid grid dimensions")?;
+
+        let body = &bytes[newline + 1..];
+        if body.len() != expected {
+            return Err(format!(
+                "expected {} bytes of grid data, found {}",
+                expected,
+                body.len()
+            ));
+        }
+        let data: Vec<f32> = body
+            .chunks(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        // Tracking needs densities between zero and a finite majorant.
+        if let Some(index) = data.iter().position(|d| !(d.is_finite() && *d >= 0.0)) {
+            return Err(format!(
+                "voxel {} has density {}, expected a finite non-negative number",
+                index, data[index]
+            ));
+        }
+        Ok(DensityGrid::new(nx, ny, nz, data, bounds))
+    }
+
+    /// Writes the grid in the format read by `load`.
+    pub fn encode(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{} {} {} {}", GRID_MAGIC, self.nx, self.ny, self.nz)?;
+        for value in &self.data {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn bounds(&self) -> Aabb {
+        self.bounds
+    }
+
+    /// The largest density anywhere in the grid, a majorant for tracking.
+    pub fn max_density(&self) -> f64 {
+        self.max_density
+    }
+
+    /// Trilinearly interpolated density at `p`, treating voxel values as
+    /// samples at voxel centers. Zero outside the bounds.
+    pub fn density(&self, p: Point3) -> f64 {
+        let (min, max) = (self.bounds.min(), self.bounds.max());
+        let dims = [self.nx, self.ny, self.nz];
+        let mut base = [0usize; 3];
+        let mut frac = [0.0; 3];
+        for axis in 0..3 {
+            if p[axis] < min[axis] || p[axis] > max[axis] {
+                return 0.0;
+            }
+            let x = (p[axis] - min[axis]) / (max[axis] - min[axis]) * dims[axis] as f64 - 0.5;
+            let x = x.clamp(0.0, (dims[axis] - 1) as f64);
+            base[axis] = (x as usize).min(dims[axis].saturating_sub(2));
+            frac[axis] = x - base[axis] as f64;
+        }
+
+        let mut sum = 0.0;
+        for corner in 0..8 {
+            let mut index = [0usize; 3];
+            let mut weight = 1.0;
+            for axis in 0..3 {
+                let upper = corner >> axis & 1 == 1;
+                index[axis] = (base[axis] + upper as usize).min(dims[axis] - 1);
+                weight *= if upper { frac[axis] } else { 1.0 - frac[axis] };
+            }
+            sum += weight * self.voxel(index[0], index[1], index[2]);
+        }
+        sum
+    }
+
+    fn voxel(&self, i: usize, j: usize, k: usize) -> f64 {
+        self.data[(k * self.ny + j) * self.nx + i] as f64
+    }
+}
+
+#[test]
+fn can_round_trip_density_grid() {
+    let bounds = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+    let grid = DensityGrid::from_fn(2, 1, 1, bounds, |p| p.x());
+    assert_eq!(grid.max_density(), 1.5);
+    assert_eq!(grid.density(Point3::new(1.0, 0.5, 0.5)), 1.0);
+    assert_eq!(grid.density(Point3::new(0.1, 0.5, 0.5)), 0.5);
+    assert_eq!(grid.density(Point3::new(3.0, 0.5, 0.5)), 0.0);
+
+    let mut bytes = Vec::new();
+    grid.encode(&mut bytes).unwrap();
+    assert!(bytes.starts_with(b"DENSITY 2 1 1\n"));
+    let decoded = DensityGrid::decode(&bytes, bounds).unwrap();
+    assert_eq!(decoded.density(Point3::new(1.25, 0.2, 0.9)), 1.25);
+
+    let err = DensityGrid::decode(&bytes[..bytes.len() - 1], bounds).err();
+    assert_eq!(
+        err.as_deref(),
+        Some("expected 8 bytes of grid data, found 7")
+    );
+
+    let huge = b"DENSITY 4294967296 4294967296 4294967296\n";
+    let err = DensityGrid::decode(huge, bounds).err();
+    assert_eq!(err.as_deref(), Some("invalid grid dimensions"));
+
+    let len = bytes.len();
+    bytes[len - 4..].copy_from_slice(&(-1.0f32).to_le_bytes());
+    let err = DensityGrid::decode(&bytes, bounds).err();
+    assert_eq!(
+        err.as_deref(),
+        Some("voxel 1 has density -1, expected a finite non-negative number")
+    );
+}
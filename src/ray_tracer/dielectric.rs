@@ -1,14 +1,116 @@
-use crate::{random, Color, Material, Ray};
+use crate::{random, Color, Material, Ray, LAMBDA_MAX, LAMBDA_MIN};
+
+/// How a dielectric's index of refraction varies with wavelength.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Dispersion {
+    /// `n = a + b / λ²`, with λ in micrometers.
+    Cauchy { a: f64, b: f64 },
+    /// `n² = 1 + Σ bᵢ λ² / (λ² - cᵢ)`, with λ in micrometers.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// Schott N-BK7, the most common optical glass.
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.03961212, 0.231792344, 1.01046945],
+        c: [0.00600069867, 0.0200179144, 103.560653],
+    };
+
+    /// Index of refraction at `wavelength` nanometers.
+    pub fn index_of_refraction(&self, wavelength: f64) -> f64 {
+        let l2 = (wavelength / 1000.0).powi(2);
+        match self {
+            Dispersion::Cauchy { a, b } => a + b / l2,
+            Dispersion::Sellmeier { b, c } => {
+                (1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>()).sqrt()
+            }
+        }
+    }
+
+    /// Whether the index is a positive finite number at every wavelength
+    /// spectral renders sample. Arbitrary coefficients can put a Sellmeier
+    /// pole among them, or make either formula's index imaginary.
+    pub fn is_valid(&self) -> bool {
+        let (l2_min, l2_max) = ((LAMBDA_MIN / 1000.0).powi(2), (LAMBDA_MAX / 1000.0).powi(2));
+        if let Dispersion::Sellmeier { c, .. } = self {
+            if c.iter().any(|&c| (l2_min..=l2_max).contains(&c)) {
+                return false;
+            }
+        }
+        (LAMBDA_MIN as u32..=LAMBDA_MAX as u32).all(|wavelength| {
+            let n = self.index_of_refraction(wavelength as f64);
+            n.is_finite() && n > 0.0
+        })
+    }
+}
+
+/// Wavelength of the helium d line, where glasses' indices are quoted.
+const D_LINE: f64 = 587.6;
 
 pub struct Dielectric {
+    /// Index used for rays without a wavelength.
     pub index_of_refraction: f64,
+    pub dispersion: Option<Dispersion>,
+    /// Absorption coefficient per unit distance travelled inside, applied
+    /// by Beer-Lambert's law as rays leave. `None` for clear glass.
+    ///
+    /// Only segments ending on this object's own back face are attenuated,
+    /// so anything placed inside, like an ice cube in a tinted drink, is
+    /// reached without absorption.
+    pub absorption: Option<Color>,
 }
 
 impl Dielectric {
     pub fn new(index_of_refraction: f64) -> Dielectric {
         Dielectric {
             index_of_refraction,
+            dispersion: None,
+            absorption: None,
+        }
+    }
+
+    /// A dispersive dielectric, which splits white light in spectral
+    /// renders. RGB renders use its index at 587.6 nm.
+    pub fn with_dispersion(dispersion: Dispersion) -> Dielectric {
+        Dielectric {
+            index_of_refraction: dispersion.index_of_refraction(D_LINE),
+            dispersion: Some(dispersion),
+            absorption: None,
+        }
+    }
+
+    /// Tints the glass, absorbing `absorption` per unit distance inside.
+    /// Fails if any channel is negative or not finite.
+    pub fn with_absorption(self, absorption: Color) -> Result<Dielectric, String> {
+        if !(0..3).all(|i| absorption[i] >= 0.0 && absorption[i].is_finite()) {
+            return Err(String::from("absorption must be finite and non-negative"));
         }
+        Ok(Dielectric {
+            absorption: Some(absorption),
+            ..self
+        })
+    }
+
+    /// Tints the glass so `transmittance` of the light remains after
+    /// travelling `distance` inside. Fails if any channel is outside
+    /// (0, 1], or `distance` is not a positive number.
+    pub fn with_transmittance(
+        self,
+        transmittance: Color,
+        distance: f64,
+    ) -> Result<Dielectric, String> {
+        if !(0..3).all(|i| transmittance[i] > 0.0 && transmittance[i] <= 1.0) {
+            return Err(String::from("transmittance must be in (0, 1]"));
+        }
+        if !(distance > 0.0 && distance.is_finite()) {
+            return Err(String::from("distance must be a positive number"));
+        }
+        let absorption = |t: f64| -t.ln() / distance;
+        self.with_absorption(Color::new(
+            absorption(transmittance.x()),
+            absorption(transmittance.y()),
+            absorption(transmittance.z()),
+        ))
     }
 
     fn reflectance(cosine: f64, ref_idx: f64) -> f64 {
@@ -21,10 +123,14 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, ray: crate::Ray, rec: crate::HitRecord) -> crate::MaterialRecord {
+        let index_of_refraction = match (self.dispersion, ray.wavelength()) {
+            (Some(dispersion), Some(wavelength)) => dispersion.index_of_refraction(wavelength),
+            _ => self.index_of_refraction,
+        };
         let refraction_ratio = if rec.front_face.unwrap() {
-            1.0 / self.index_of_refraction
+            1.0 / index_of_refraction
         } else {
-            self.index_of_refraction
+            index_of_refraction
         };
 
         let unit_direction = ray.direction().unit_vector();
@@ -39,10 +145,93 @@ impl Material for Dielectric {
                 unit_direction.refract(rec.normal.unwrap(), refraction_ratio)
             };
 
+        // Hitting the inside, the ray has crossed the medium from its last
+        // hit, whether it now leaves or reflects back in.
+        let attenuation = match self.absorption {
+            Some(absorption) if !rec.front_face.unwrap() => {
+                let distance = rec.t.unwrap() * ray.direction().length();
+                Color::new(
+                    (-absorption.x() * distance).exp(),
+                    (-absorption.y() * distance).exp(),
+                    (-absorption.z() * distance).exp(),
+                )
+            }
+            _ => Color::new(1.0, 1.0, 1.0),
+        };
+
         crate::MaterialRecord {
-            attenuation: Color::new(1.0, 1.0, 1.0),
-            scattered: Some(Ray::new(rec.p.unwrap(), direction)),
+            attenuation,
+            scattered: Some(Ray::with_time(rec.p.unwrap(), direction, ray.time())),
+            pdf: None,
             scatter: true,
         }
     }
 }
+
+#[test]
+fn dispersion_bends_blue_more_than_red() {
+    let n = |wavelength| Dispersion::BK7.index_of_refraction(wavelength);
+    assert!((n(D_LINE) - 1.5168).abs() < 1e-4);
+    assert!(n(450.0) > n(650.0));
+
+    let cauchy = Dispersion::Cauchy { a: 1.5, b: 0.01 };
+    assert!((cauchy.index_of_refraction(500.0) - 1.54).abs() < 1e-12);
+    let glass = Dielectric::with_dispersion(cauchy);
+    assert_eq!(
+        glass.index_of_refraction,
+        cauchy.index_of_refraction(D_LINE)
+    );
+
+    assert!(Dispersion::BK7.is_valid() && cauchy.is_valid());
+    let pole = Dispersion::Sellmeier {
+        b: [1.0, 0.0, 0.0],
+        c: [0.25, 0.0, 0.0],
+    };
+    assert!(!pole.index_of_refraction(500.0).is_finite());
+    assert!(!pole.is_valid());
+    assert!(!Dispersion::Cauchy { a: -1.0, b: 0.01 }.is_valid());
+}
+
+#[test]
+fn absorption_depends_on_distance_inside() {
+    use crate::{HitRecord, Point3, Vec3};
+
+    let glass = Dielectric::new(1.5)
+        .with_transmittance(Color::new(0.5, 1.0, 0.25), 2.0)
+        .unwrap();
+    let mut rec = HitRecord::new();
+    rec.p = Some(Point3::new(0.0, 0.0, 4.0));
+    rec.t = Some(2.0);
+    rec.set_face_normal(
+        &Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0)),
+        Vec3::new(0.0, 0.0, 1.0),
+    );
+
+    // Leaving after four units inside.
+    let ray = Ray::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 2.0));
+    let attenuation = glass.scatter(ray, rec.clone()).attenuation;
+    assert!((attenuation - Color::new(0.25, 1.0, 0.0625)).near_zero());
+
+    // Entering is free.
+    rec.set_face_normal(&ray, Vec3::new(0.0, 0.0, -1.0));
+    let attenuation = glass.scatter(ray, rec).attenuation;
+    assert_eq!(attenuation, Color::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn rejects_invalid_tints() {
+    let glass = || Dielectric::new(1.5);
+    let err = glass()
+        .with_transmittance(Color::new(0.5, 0.0, 0.5), 1.0)
+        .err();
+    assert_eq!(err.as_deref(), Some("transmittance must be in (0, 1]"));
+    let err = glass()
+        .with_transmittance(Color::new(0.5, 0.5, 0.5), 0.0)
+        .err();
+    assert_eq!(err.as_deref(), Some("distance must be a positive number"));
+    let err = glass().with_absorption(Color::new(0.0, -1.0, 0.0)).err();
+    assert_eq!(
+        err.as_deref(),
+        Some("absorption must be finite and non-negative")
+    );
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::{Color, HitRecord, Material, MaterialRecord, Ray, SolidColor, Texture};
+
+/// Emits light uniformly from both sides of a surface and scatters nothing.
+pub struct DiffuseLight {
+    pub emit: Arc<dyn Texture>,
+}
+
+impl DiffuseLight {
+    pub fn new(emit: Color) -> DiffuseLight {
+        DiffuseLight::from_texture(Arc::new(SolidColor::new(emit)))
+    }
+
+    pub fn from_texture(emit: Arc<dyn Texture>) -> DiffuseLight {
+        DiffuseLight { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray: Ray, _rec: HitRecord) -> MaterialRecord {
+        MaterialRecord::absorbed()
+    }
+
+    fn emitted(&self, _ray: &Ray, rec: &HitRecord) -> Color {
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        self.emit.value(u, v, rec.p.unwrap())
+    }
+}
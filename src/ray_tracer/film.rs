@@ -0,0 +1,193 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+use image::{codecs::png::PngEncoder, ExtendedColorType, ImageEncoder};
+
+use crate::{
+    hdr::{write_exr, write_hdr},
+    Color, ToneMap,
+};
+
+/// Encodings a `Film` can be written in.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ImageFormat {
+    Png,
+    /// Binary PPM (P6).
+    Ppm,
+    /// Plain-text PPM (P3).
+    PlainPpm,
+    /// OpenEXR with 32-bit float channels, storing linear radiance.
+    Exr,
+    /// Radiance RGBE, storing linear radiance.
+    Hdr,
+}
+
+impl ImageFormat {
+    /// Picks a format from a file extension: `.png`, `.ppm` (binary),
+    /// `.pnm`/`.txt` (plain text), `.exr` or `.hdr`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<ImageFormat> {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "pnm" | "txt" => Some(ImageFormat::PlainPpm),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" => Some(ImageFormat::Hdr),
+            _ => None,
+        }
+    }
+
+    /// Parses a format name as used on the command line.
+    pub fn from_name(name: &str) -> Option<ImageFormat> {
+        match name {
+            "png" => Some(ImageFormat::Png),
+            "ppm" => Some(ImageFormat::Ppm),
+            "ppm-plain" => Some(ImageFormat::PlainPpm),
+            "exr" => Some(ImageFormat::Exr),
+            "hdr" => Some(ImageFormat::Hdr),
+            _ => None,
+        }
+    }
+}
+
+/// Framebuffer accumulating color samples for each pixel.
+///
+/// Pixels are addressed from the top-left corner of the image.
+pub struct Film {
+    width: usize,
+    height: usize,
+    sums: Vec<Color>,
+    counts: Vec<usize>,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Film {
+        Film {
+            width,
+            height,
+            sums: vec![Color::new(0.0, 0.0, 0.0); width * height],
+            counts: vec![0; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn add_sample(&mut self, x: usize, y: usize, color: Color) {
+        self.add_samples(x, y, color, 1);
+    }
+
+    /// Adds `count` samples whose colors sum to `sum`.
+    pub fn add_samples(&mut self, x: usize, y: usize, sum: Color, count: usize) {
+        let i = y * self.width + x;
+        self.sums[i] += sum;
+        self.counts[i] += count;
+    }
+
+    /// Returns the average of the samples taken for a pixel.
+    pub fn pixel(&self, x: usize, y: usize) -> Color {
+        let i = y * self.width + x;
+        match self.counts[i] {
+            0 => Color::new(0.0, 0.0, 0.0),
+            n => self.sums[i] / n as f64,
+        }
+    }
+
+    /// Returns tone mapped 8-bit RGB values, top row first.
+    pub fn to_rgb8(&self, tone_map: &ToneMap) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.width * self.height * 3);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                bytes.extend(tone_map.to_rgb8(self.pixel(x, y)));
+            }
+        }
+        bytes
+    }
+
+    /// Writes the image to `path`, choosing the format from its extension.
+    ///
+    /// `tone_map` is ignored by the floating point formats.
+    pub fn write(&self, path: impl AsRef<Path>, tone_map: &ToneMap) -> io::Result<()> {
+        let path = path.as_ref();
+        let format = ImageFormat::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown image format for '{}'", path.display()),
+            )
+        })?;
+        let mut out = BufWriter::new(File::create(path)?);
+        self.encode(&mut out, format, tone_map)?;
+        out.flush()
+    }
+
+    pub fn encode(
+        &self,
+        out: &mut impl Write,
+        format: ImageFormat,
+        tone_map: &ToneMap,
+    ) -> io::Result<()> {
+        match format {
+            ImageFormat::Exr => write_exr(out, self),
+            ImageFormat::Hdr => write_hdr(out, self),
+            ImageFormat::Png => PngEncoder::new(out)
+                .write_image(
+                    &self.to_rgb8(tone_map),
+                    self.width as u32,
+                    self.height as u32,
+                    ExtendedColorType::Rgb8,
+                )
+                .map_err(io::Error::other),
+            ImageFormat::Ppm => {
+                write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+                out.write_all(&self.to_rgb8(tone_map))
+            }
+            ImageFormat::PlainPpm => {
+                write!(out, "P3\n{} {}\n255\n", self.width, self.height)?;
+                for pixel in self.to_rgb8(tone_map).chunks(3) {
+                    writeln!(out, "{} {} {}", pixel[0], pixel[1], pixel[2])?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[test]
+fn film_averages_samples() {
+    let mut film = Film::new(2, 1);
+    film.add_sample(1, 0, Color::new(1.0, 0.0, 0.0));
+    film.add_sample(1, 0, Color::new(0.0, 0.5, 0.0));
+    assert_eq!(film.pixel(1, 0), Color::new(0.5, 0.25, 0.0));
+    assert_eq!(film.pixel(0, 0), Color::new(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn can_encode_ppm() {
+    let mut film = Film::new(2, 1);
+    film.add_samples(0, 0, Color::new(4.0, 1.0, 0.0), 4);
+    let mut plain = Vec::new();
+    film.encode(&mut plain, ImageFormat::PlainPpm, &ToneMap::default())
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(plain).unwrap(),
+        "P3\n2 1\n255\n255 128 0\n0 0 0\n"
+    );
+
+    let mut binary = Vec::new();
+    film.encode(&mut binary, ImageFormat::Ppm, &ToneMap::default())
+        .unwrap();
+    assert_eq!(binary, b"P6\n2 1\n255\n\xff\x80\x00\x00\x00\x00");
+
+    let mut png = Vec::new();
+    film.encode(&mut png, ImageFormat::Png, &ToneMap::default())
+        .unwrap();
+    assert_eq!(&png[1..4], b"PNG");
+}
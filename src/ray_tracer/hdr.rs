@@ -0,0 +1,161 @@
+//! Writers for floating point image formats, which store the linear radiance
+//! of a `Film` without tone mapping.
+
+use std::io::{self, Write};
+
+use crate::{Color, Film};
+
+const EXR_MAGIC: [u8; 4] = [0x76, 0x2f, 0x31, 0x01];
+const EXR_PIXEL_TYPE_FLOAT: i32 = 2;
+
+/// Writes a single-part, uncompressed scanline OpenEXR image with 32-bit
+/// float R, G and B channels.
+pub(crate) fn write_exr(out: &mut impl Write, film: &Film) -> io::Result<()> {
+    let (width, height) = (film.width(), film.height());
+
+    let mut header = Vec::new();
+    header.extend(EXR_MAGIC);
+    header.extend(2i32.to_le_bytes());
+
+    // Channels must be listed in alphabetical order.
+    let mut channels = Vec::new();
+    for name in ["B", "G", "R"] {
+        channels.extend(name.as_bytes());
+        channels.push(0);
+        channels.extend(EXR_PIXEL_TYPE_FLOAT.to_le_bytes());
+        channels.extend([0u8; 4]); // pLinear and reserved bytes
+        channels.extend(1i32.to_le_bytes()); // x sampling
+        channels.extend(1i32.to_le_bytes()); // y sampling
+    }
+    channels.push(0);
+    exr_attribute(&mut header, "channels", "chlist", &channels);
+
+    exr_attribute(&mut header, "compression", "compression", &[0]);
+
+    let mut window = Vec::new();
+    for v in [0, 0, width as i32 - 1, height as i32 - 1] {
+        window.extend(v.to_le_bytes());
+    }
+    exr_attribute(&mut header, "dataWindow", "box2i", &window);
+    exr_attribute(&mut header, "displayWindow", "box2i", &window);
+
+    exr_attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+    exr_attribute(
+        &mut header,
+        "pixelAspectRatio",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    exr_attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+    exr_attribute(
+        &mut header,
+        "screenWindowWidth",
+        "float",
+        &1f32.to_le_bytes(),
+    );
+    header.push(0);
+    out.write_all(&header)?;
+
+    // Offset table, with one scanline per chunk.
+    let chunk_size = 8 + width * 3 * 4;
+    let first_chunk = header.len() + height * 8;
+    for y in 0..height {
+        out.write_all(&((first_chunk + y * chunk_size) as u64).to_le_bytes())?;
+    }
+
+    let mut chunk = Vec::with_capacity(chunk_size);
+    for y in 0..height {
+        chunk.clear();
+        chunk.extend((y as i32).to_le_bytes());
+        chunk.extend(((width * 3 * 4) as i32).to_le_bytes());
+        for channel in [2, 1, 0] {
+            for x in 0..width {
+                chunk.extend((film.pixel(x, y)[channel] as f32).to_le_bytes());
+            }
+        }
+        out.write_all(&chunk)?;
+    }
+
+    Ok(())
+}
+
+fn exr_attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+    header.extend(name.as_bytes());
+    header.push(0);
+    header.extend(kind.as_bytes());
+    header.push(0);
+    header.extend((value.len() as i32).to_le_bytes());
+    header.extend(value);
+}
+
+/// Writes a Radiance RGBE image with flat (uncompressed) scanlines.
+pub(crate) fn write_hdr(out: &mut impl Write, film: &Film) -> io::Result<()> {
+    write!(
+        out,
+        "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+        film.height(),
+        film.width()
+    )?;
+
+    let mut line = Vec::with_capacity(film.width() * 4);
+    for y in 0..film.height() {
+        line.clear();
+        for x in 0..film.width() {
+            line.extend(to_rgbe(film.pixel(x, y)));
+        }
+        out.write_all(&line)?;
+    }
+
+    Ok(())
+}
+
+/// Encodes a color as three 8-bit mantissas sharing an exponent.
+fn to_rgbe(color: Color) -> [u8; 4] {
+    let (r, g, b) = (color.x().max(0.0), color.y().max(0.0), color.z().max(0.0));
+    let v = r.max(g).max(b);
+    if v < 1e-32 || !v.is_finite() {
+        return [0, 0, 0, 0];
+    }
+
+    // v = m * 2^e with m in [0.5, 1).
+    let mut e = v.log2().floor() as i32 + 1;
+    let mut m = v / 2f64.powi(e);
+    if m >= 1.0 {
+        m /= 2.0;
+        e += 1;
+    }
+    let scale = m * 256.0 / v;
+    [
+        (r * scale) as u8,
+        (g * scale) as u8,
+        (b * scale) as u8,
+        (e + 128) as u8,
+    ]
+}
+
+#[test]
+fn can_encode_rgbe() {
+    assert_eq!(to_rgbe(Color::new(0.0, 0.0, 0.0)), [0, 0, 0, 0]);
+    assert_eq!(to_rgbe(Color::new(1.0, 0.5, 0.25)), [128, 64, 32, 129]);
+    assert_eq!(to_rgbe(Color::new(12.0, 0.0, 3.0)), [192, 0, 48, 132]);
+}
+
+#[test]
+fn can_encode_exr() {
+    let mut film = Film::new(3, 2);
+    film.add_sample(2, 1, Color::new(8.5, 0.25, -1.0));
+    let mut exr = Vec::new();
+    write_exr(&mut exr, &film).unwrap();
+    assert_eq!(exr[..4], EXR_MAGIC);
+
+    // The last chunk holds the bottom row, with the final float being R of (2, 1).
+    let chunk_size = 8 + 3 * 3 * 4;
+    let chunk = &exr[exr.len() - chunk_size..];
+    assert_eq!(chunk[..4], 1i32.to_le_bytes());
+    assert_eq!(chunk[chunk_size - 4..], 8.5f32.to_le_bytes());
+    assert_eq!(chunk[8 + 8..12 + 8], (-1f32).to_le_bytes());
+
+    // Both offsets point just past the table.
+    let table = exr.len() - 2 * chunk_size - 16;
+    assert_eq!(exr[table..table + 8], (table as u64 + 16).to_le_bytes());
+}
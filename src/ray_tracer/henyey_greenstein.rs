@@ -0,0 +1,129 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{random, Color, HitRecord, Material, Onb, Pdf, Ray, SolidColor, Texture, Vec3};
+
+/// Henyey–Greenstein phase function for participating media.
+///
+/// The anisotropy `g` in (-1, 1) is the mean cosine of the scattering
+/// angle: positive values scatter forward, negative values backward, and 0
+/// is isotropic.
+pub struct HenyeyGreenstein {
+    pub albedo: Arc<dyn Texture>,
+    pub g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(color: Color, g: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein::from_texture(Arc::new(SolidColor::new(color)), g)
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>, g: f64) -> HenyeyGreenstein {
+        HenyeyGreenstein {
+            albedo,
+            g: g.clamp(-0.999, 0.999),
+        }
+    }
+
+    /// Probability density, per steradian, of scattering by an angle whose
+    /// cosine is `cos_theta`.
+    pub fn phase(&self, cos_theta: f64) -> f64 {
+        phase(self.g, cos_theta)
+    }
+
+    /// Samples a new direction around the propagation `direction`.
+    pub fn sample(&self, direction: Vec3) -> Vec3 {
+        PhasePdf::new(self.g, direction).generate()
+    }
+}
+
+fn phase(g: f64, cos_theta: f64) -> f64 {
+    let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+    (1.0 - g * g) / (4.0 * PI * denom * denom.sqrt())
+}
+
+/// Scattered directions around an incoming ray, distributed by the phase
+/// function.
+struct PhasePdf {
+    g: f64,
+    uvw: Onb,
+}
+
+impl PhasePdf {
+    fn new(g: f64, direction: Vec3) -> PhasePdf {
+        PhasePdf {
+            g,
+            uvw: Onb::from_w(direction),
+        }
+    }
+}
+
+impl Pdf for PhasePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        phase(self.g, direction.unit_vector().dot(self.uvw.w))
+    }
+
+    fn generate(&self) -> Vec3 {
+        let g = self.g;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * random()
+        } else {
+            let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * random());
+            ((1.0 + g * g - s * s) / (2.0 * g)).clamp(-1.0, 1.0)
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = 2.0 * PI * random();
+        self.uvw.local(Vec3::new(
+            sin_theta * phi.cos(),
+            sin_theta * phi.sin(),
+            cos_theta,
+        ))
+    }
+}
+
+impl Material for HenyeyGreenstein {
+    fn scatter(&self, ray: crate::Ray, rec: crate::HitRecord) -> crate::MaterialRecord {
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        let pdf = PhasePdf::new(self.g, ray.direction());
+        crate::MaterialRecord {
+            attenuation: self.albedo.value(u, v, rec.p.unwrap()),
+            scattered: Some(Ray::with_time(rec.p.unwrap(), pdf.generate(), ray.time())),
+            scatter: true,
+            pdf: Some(Box::new(pdf)),
+        }
+    }
+
+    fn scattering_pdf(&self, ray: &Ray, _rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cos_theta = ray
+            .direction()
+            .unit_vector()
+            .dot(scattered.direction().unit_vector());
+        self.phase(cos_theta)
+    }
+}
+
+#[test]
+fn sampled_mean_cosine_matches_g() {
+    crate::seed_thread_rng(Some(11));
+    let direction = Vec3::new(0.0, 0.6, 0.8);
+    for g in [-0.5, 0.0, 0.8] {
+        let phase = HenyeyGreenstein::new(Color::new(1.0, 1.0, 1.0), g);
+        let samples = 20_000;
+        let mean = (0..samples)
+            .map(|_| phase.sample(direction).dot(direction))
+            .sum::<f64>()
+            / samples as f64;
+        assert!((mean - g).abs() < 0.02, "g = {}: {}", g, mean);
+    }
+    crate::seed_thread_rng(None);
+
+    // The phase function integrates to 1 over the sphere.
+    let phase = HenyeyGreenstein::new(Color::new(1.0, 1.0, 1.0), 0.7);
+    let steps = 10_000;
+    let integral: f64 = (0..steps)
+        .map(|i| {
+            let cos_theta = -1.0 + 2.0 * (i as f64 + 0.5) / steps as f64;
+            2.0 * PI * phase.phase(cos_theta) * 2.0 / steps as f64
+        })
+        .sum();
+    assert!((integral - 1.0).abs() < 1e-3, "{}", integral);
+}
@@ -0,0 +1,170 @@
+use std::sync::Arc;
+
+use crate::{random, Aabb, DensityGrid, HitRecord, Hittable, Material, Ray, Vec3};
+
+/// A participating medium whose density varies through a `DensityGrid`.
+///
+/// Scattering distances are sampled with delta (Woodcock) tracking against
+/// the grid's maximum density, which is unbiased without stepping through
+/// every voxel.
+pub struct HeterogeneousMedium {
+    pub grid: Arc<DensityGrid>,
+    /// Multiplies the grid's values to give extinction per unit length.
+    pub density_scale: f64,
+    pub phase_function: Arc<dyn Material>,
+    majorant: f64,
+}
+
+impl HeterogeneousMedium {
+    /// # Panics
+    ///
+    /// If `density_scale` is not a positive number.
+    pub fn new(
+        grid: Arc<DensityGrid>,
+        density_scale: f64,
+        phase_function: Arc<dyn Material>,
+    ) -> HeterogeneousMedium {
+        assert!(
+            density_scale > 0.0,
+            "medium density scale must be positive, got {}",
+            density_scale
+        );
+        let majorant = grid.max_density() * density_scale;
+        HeterogeneousMedium {
+            grid,
+            density_scale,
+            phase_function,
+            majorant,
+        }
+    }
+
+    /// Extinction coefficient at `p`.
+    pub fn density(&self, p: Vec3) -> f64 {
+        self.grid.density(p) * self.density_scale
+    }
+}
+
+impl Hittable for HeterogeneousMedium {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let (t_enter, t_exit) = match self.grid.bounds().intersect(ray, t_min, t_max) {
+            Some(span) => span,
+            None => return false,
+        };
+        if self.majorant <= 0.0 {
+            return false;
+        }
+
+        // Delta tracking: take exponential steps through a homogenized
+        // medium of majorant density, accepting a collision as real with
+        // probability density / majorant.
+        let step = 1.0 / (self.majorant * ray.direction().length());
+        let mut t = t_enter;
+        loop {
+            t -= (1.0 - random()).ln() * step;
+            if t >= t_exit {
+                return false;
+            }
+            if random() * self.majorant < self.density(ray.at(t)) {
+                break;
+            }
+        }
+
+        rec.t = Some(t);
+        rec.p = Some(ray.at(t));
+        // Arbitrary, since the phase function ignores the surface.
+        rec.normal = Some(Vec3::new(1.0, 0.0, 0.0));
+        rec.front_face = Some(true);
+        rec.material = Some(self.phase_function.clone());
+        rec.barycentric = None;
+        rec.uv = None;
+
+        true
+    }
+
+    fn hit_surface(&self, _ray: Ray, _t_min: f64, _t_max: f64, _rec: &mut HitRecord) -> bool {
+        false
+    }
+
+    /// Ratio tracking: the same exponential steps as delta tracking, but
+    /// weighting by the chance of passing each collision instead of
+    /// stopping, for a smoother estimate.
+    fn transmittance(&self, ray: Ray, t_min: f64, t_max: f64) -> f64 {
+        let (t_enter, t_exit) = match self.grid.bounds().intersect(ray, t_min, t_max) {
+            Some(span) => span,
+            None => return 1.0,
+        };
+        if self.majorant <= 0.0 {
+            return 1.0;
+        }
+
+        let step = 1.0 / (self.majorant * ray.direction().length());
+        let mut transmittance = 1.0;
+        let mut t = t_enter;
+        loop {
+            t -= (1.0 - random()).ln() * step;
+            if t >= t_exit {
+                return transmittance;
+            }
+            transmittance *= 1.0 - self.density(ray.at(t)) / self.majorant;
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.grid.bounds())
+    }
+}
+
+#[test]
+fn tracking_matches_beer_lambert() {
+    use crate::{Color, Isotropic, Point3};
+
+    crate::seed_thread_rng(Some(3));
+    // Density 1 in the left half of the box and 0.25 in the right half.
+    let bounds = Aabb::new(Point3::new(0.0, 0.0, 0.0), Point3::new(2.0, 1.0, 1.0));
+    let grid = DensityGrid::new(2, 1, 1, vec![1.0, 0.25], bounds);
+    let medium = HeterogeneousMedium::new(
+        Arc::new(grid),
+        2.0,
+        Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0))),
+    );
+
+    // Along a line through the voxel centers' plane the density is a ramp
+    // from 2 to 0.5 between x = 0.5 and 1.5, and constant outside it.
+    let ray = Ray::new(Point3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+    let optical_depth = 2.0 * 0.5 + (2.0 + 0.5) / 2.0 + 0.5 * 0.5;
+    let expected = f64::exp(-optical_depth);
+
+    let samples = 20_000;
+    let ratio = (0..samples)
+        .map(|_| medium.transmittance(ray, 0.0, f64::INFINITY))
+        .sum::<f64>()
+        / samples as f64;
+    let passed = (0..samples)
+        .filter(|_| !medium.hit(ray, 0.0, f64::INFINITY, &mut HitRecord::new()))
+        .count() as f64
+        / samples as f64;
+    crate::seed_thread_rng(None);
+
+    assert!((ratio - expected).abs() < 0.01, "{} vs {}", ratio, expected);
+    assert!(
+        (passed - expected).abs() < 0.01,
+        "{} vs {}",
+        passed,
+        expected
+    );
+
+    // Shadow rays pass through the medium to the surface behind it.
+    let medium: Arc<dyn Hittable> = Arc::new(medium);
+    let wall: Arc<dyn Hittable> = Arc::new(crate::Quad::new(
+        Point3::new(3.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        Arc::new(Isotropic::new(Color::new(1.0, 1.0, 1.0))),
+    ));
+    let world = crate::HittableList {
+        objects: vec![medium, wall],
+    };
+    let mut rec = HitRecord::new();
+    assert!(world.hit_surface(ray, 0.0, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(4.0));
+}
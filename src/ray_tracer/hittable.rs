@@ -1,13 +1,18 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::{Material, Point3, Ray, Vec3};
+use crate::{Aabb, Material, Point3, Ray, Vec3};
 
+#[derive(Clone)]
 pub struct HitRecord {
     pub p: Option<Point3>,
     pub normal: Option<Vec3>,
     pub t: Option<f64>,
     pub front_face: Option<bool>,
-    pub material: Option<Rc<dyn Material>>,
+    pub material: Option<Arc<dyn Material>>,
+    /// Barycentric coordinates (b1, b2) of the hit on a triangle.
+    pub barycentric: Option<(f64, f64)>,
+    /// Surface texture coordinates of the hit.
+    pub uv: Option<(f64, f64)>,
 }
 
 impl HitRecord {
@@ -18,8 +23,11 @@ impl HitRecord {
             t: None,
             front_face: None,
             material: None,
+            barycentric: None,
+            uv: None,
         }
     }
+
     pub fn set_face_normal(&mut self, ray: &Ray, outward_normal: Vec3) {
         self.front_face = Some(ray.direction().dot(outward_normal) < 0.0);
         self.normal = if self.front_face.unwrap() {
@@ -34,14 +42,50 @@ impl HitRecord {
     }
 
     pub fn set_rec(&mut self, rec: &HitRecord) {
-        self.p = rec.p.clone();
-        self.t = rec.t.clone();
-        self.normal = rec.normal.clone();
-        self.front_face = rec.front_face.clone();
+        self.p = rec.p;
+        self.t = rec.t;
+        self.normal = rec.normal;
+        self.front_face = rec.front_face;
         self.material = rec.material.clone();
+        self.barycentric = rec.barycentric;
+        self.uv = rec.uv;
+    }
+}
+
+impl Default for HitRecord {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-pub trait Hittable {
+pub trait Hittable: Send + Sync {
     fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool;
+
+    /// Returns a box enclosing the object, or `None` if it is unbounded.
+    fn bounding_box(&self) -> Option<Aabb>;
+
+    /// Like `hit`, but passes through participating media, so shadow rays
+    /// can find the surface they end on and account for media with
+    /// `transmittance` instead.
+    fn hit_surface(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.hit(ray, t_min, t_max, rec)
+    }
+
+    /// Fraction of light carried unscattered along the ray between `t_min`
+    /// and `t_max` through participating media. Surfaces return 1.
+    fn transmittance(&self, _ray: Ray, _t_min: f64, _t_max: f64) -> f64 {
+        1.0
+    }
+
+    /// Density, per steradian, of `random` choosing `direction` from
+    /// `origin`. Objects that can't be sampled, and so can't be used as
+    /// lights, return 0.
+    fn pdf_value(&self, _origin: Point3, _direction: Vec3) -> f64 {
+        0.0
+    }
+
+    /// Returns a random direction from `origin` toward the object.
+    fn random(&self, _origin: Point3) -> Vec3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    }
 }
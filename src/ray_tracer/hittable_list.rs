@@ -1,14 +1,14 @@
-use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::{HitRecord, Hittable};
+use crate::{aabb::surrounding_box, random, Aabb, HitRecord, Hittable, Point3, Vec3};
 
-pub struct HittableList<T: Hittable> {
-    pub objects: Vec<Rc<T>>,
+pub struct HittableList<T: Hittable + ?Sized> {
+    pub objects: Vec<Arc<T>>,
 }
 
 impl<T> Hittable for HittableList<T>
 where
-    T: Hittable,
+    T: Hittable + ?Sized,
 {
     fn hit(&self, ray: crate::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
         let mut temp_rec = HitRecord::new();
@@ -25,4 +25,57 @@ where
 
         hit_anything
     }
+
+    fn hit_surface(&self, ray: crate::Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let mut temp_rec = HitRecord::new();
+        let mut hit_anything = false;
+        let mut closest_so_far = t_max;
+
+        for obj in &self.objects {
+            if obj.hit_surface(ray, t_min, closest_so_far, &mut temp_rec) {
+                hit_anything = true;
+                closest_so_far = temp_rec.t().unwrap();
+                rec.set_rec(&temp_rec);
+            }
+        }
+
+        hit_anything
+    }
+
+    fn transmittance(&self, ray: crate::Ray, t_min: f64, t_max: f64) -> f64 {
+        self.objects
+            .iter()
+            .map(|obj| obj.transmittance(ray, t_min, t_max))
+            .product()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut output_box: Option<Aabb> = None;
+        for obj in &self.objects {
+            let bbox = obj.bounding_box()?;
+            output_box = Some(match output_box {
+                Some(acc) => surrounding_box(&acc, &bbox),
+                None => bbox,
+            });
+        }
+        output_box
+    }
+
+    /// Picks one of the objects at random, so lights can be sampled as a
+    /// group.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let weight = 1.0 / self.objects.len() as f64;
+        self.objects
+            .iter()
+            .map(|obj| weight * obj.pdf_value(origin, direction))
+            .sum()
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        if self.objects.is_empty() {
+            return Vec3::new(1.0, 0.0, 0.0);
+        }
+        let index = (random() * self.objects.len() as f64) as usize;
+        self.objects[index.min(self.objects.len() - 1)].random(origin)
+    }
 }
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+
+use crate::{Aabb, HitRecord, Hittable, Matrix4, Point3, Ray, Vec3};
+
+/// Places a shared `Hittable` in the world through an affine transform.
+///
+/// Rays are moved into object space rather than the object into world space,
+/// so any number of instances can share one object and its BVH.
+pub struct Instance {
+    pub object: Arc<dyn Hittable>,
+    to_world: Matrix4,
+    to_object: Matrix4,
+    /// Inverse transpose of `to_world`, for transforming normals.
+    normal_matrix: Matrix4,
+    bbox: Option<Aabb>,
+}
+
+impl Instance {
+    /// Panics if `to_world` is not invertible.
+    pub fn new(object: Arc<dyn Hittable>, to_world: Matrix4) -> Instance {
+        let to_object = to_world
+            .inverse()
+            .expect("instance transform is not invertible");
+        let bbox = object.bounding_box().map(|bbox| {
+            let (min, max) = (bbox.min(), bbox.max());
+            let mut lo = Point3::new(f64::INFINITY, f64::INFINITY, f64::INFINITY);
+            let mut hi = -lo;
+            for corner in 0..8 {
+                let pick = |axis: usize| {
+                    if corner >> axis & 1 == 0 {
+                        min[axis]
+                    } else {
+                        max[axis]
+                    }
+                };
+                let p = to_world.transform_point(Point3::new(pick(0), pick(1), pick(2)));
+                lo = Point3::new(lo.x().min(p.x()), lo.y().min(p.y()), lo.z().min(p.z()));
+                hi = Point3::new(hi.x().max(p.x()), hi.y().max(p.y()), hi.z().max(p.z()));
+            }
+            Aabb::new(lo, hi)
+        });
+        Instance {
+            object,
+            to_world,
+            to_object,
+            normal_matrix: to_object.transpose(),
+            bbox,
+        }
+    }
+
+    pub fn transform(&self) -> Matrix4 {
+        self.to_world
+    }
+
+    /// Moves `ray` into object space. The direction is left unnormalized so
+    /// t means the same in both spaces.
+    fn object_ray(&self, ray: Ray) -> Ray {
+        Ray::with_time(
+            self.to_object.transform_point(ray.origin()),
+            self.to_object.transform_vector(ray.direction()),
+            ray.time(),
+        )
+    }
+
+    /// Moves a hit found in object space back into world space.
+    fn hit_to_world(&self, rec: &mut HitRecord) {
+        // Normals go through the inverse transpose, which keeps their side
+        // relative to the ray, so front_face carries over unchanged.
+        rec.p = rec.p.map(|p| self.to_world.transform_point(p));
+        rec.normal = rec
+            .normal
+            .map(|n| self.normal_matrix.transform_vector(n).unit_vector());
+    }
+}
+
+impl Hittable for Instance {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self.object.hit(self.object_ray(ray), t_min, t_max, rec) {
+            return false;
+        }
+        self.hit_to_world(rec);
+        true
+    }
+
+    fn hit_surface(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        if !self
+            .object
+            .hit_surface(self.object_ray(ray), t_min, t_max, rec)
+        {
+            return false;
+        }
+        self.hit_to_world(rec);
+        true
+    }
+
+    fn transmittance(&self, ray: Ray, t_min: f64, t_max: f64) -> f64 {
+        self.object
+            .transmittance(self.object_ray(ray), t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bbox
+    }
+
+    /// The object's density, converted to world-space solid angle. A linear
+    /// map `M` taking unit direction `w` to `M w / |M w|` stretches solid
+    /// angle by `|det M| / |M w|^3`.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let object_direction = self.to_object.transform_vector(direction);
+        let stretch = object_direction.length() / direction.length();
+        let object_pdf = self
+            .object
+            .pdf_value(self.to_object.transform_point(origin), object_direction);
+        object_pdf * self.to_object.linear_determinant().abs() / stretch.powi(3)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let direction = self.object.random(self.to_object.transform_point(origin));
+        self.to_world.transform_vector(direction)
+    }
+}
+
+#[test]
+fn instance_transforms_hits() {
+    use crate::{Color, Lambertian, Sphere};
+
+    let material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let sphere = Arc::new(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, material));
+    let instance = Instance::new(
+        sphere,
+        Matrix4::translation(Vec3::new(5.0, 0.0, 0.0)) * Matrix4::scaling(Vec3::new(2.0, 1.0, 1.0)),
+    );
+
+    // Hit the stretched side of the ellipsoid head on.
+    let mut rec = HitRecord::new();
+    let ray = Ray::new(Point3::new(10.0, 0.0, 0.0), Vec3::new(-1.0, 0.0, 0.0));
+    assert!(instance.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(3.0));
+    assert_eq!(rec.p, Some(Point3::new(7.0, 0.0, 0.0)));
+    assert_eq!(rec.normal, Some(Vec3::new(1.0, 0.0, 0.0)));
+
+    // An oblique hit has its normal skewed by the inverse transpose.
+    let ray = Ray::new(
+        Point3::new(5.0 + 2.0 * 0.6, 5.0, 0.0),
+        Vec3::new(0.0, -1.0, 0.0),
+    );
+    assert!(instance.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    let n = rec.normal.unwrap();
+    assert!((n - Vec3::new(0.3, 0.8, 0.0).unit_vector()).length() < 1e-9);
+
+    let bbox = instance.bounding_box().unwrap();
+    assert_eq!(bbox.min(), Point3::new(3.0, -1.0, -1.0));
+    assert_eq!(bbox.max(), Point3::new(7.0, 1.0, 1.0));
+}
+
+#[test]
+fn instance_pdf_survives_scaling() {
+    use crate::{Color, DiffuseLight, Quad};
+
+    // A stretched, turned and moved unit square, and the same square built
+    // in place, must be sampled with the same density.
+    let light = Arc::new(DiffuseLight::new(Color::new(1.0, 1.0, 1.0)));
+    let square = Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 1.0),
+        light.clone(),
+    );
+    let transform = Matrix4::translation(Vec3::new(-1.0, 3.0, 0.5))
+        * Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), 30.0)
+        * Matrix4::scaling(Vec3::new(3.0, 1.0, 0.5));
+    let instance = Instance::new(Arc::new(square), transform);
+    let placed = Quad::new(
+        transform.transform_point(Point3::new(0.0, 0.0, 0.0)),
+        transform.transform_vector(Vec3::new(1.0, 0.0, 0.0)),
+        transform.transform_vector(Vec3::new(0.0, 0.0, 1.0)),
+        light,
+    );
+
+    let origin = Point3::new(0.0, 0.0, 0.0);
+    for _ in 0..10 {
+        let direction = placed.random(origin);
+        let expected = placed.pdf_value(origin, direction);
+        let pdf = instance.pdf_value(origin, 2.0 * direction);
+        assert!(
+            (pdf - expected).abs() < 1e-9 * expected,
+            "{} vs {}",
+            pdf,
+            expected
+        );
+    }
+}
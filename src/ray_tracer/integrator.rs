@@ -0,0 +1,543 @@
+use crate::{
+    random, rgb_to_spectrum, spectral_sample_to_rgb, Color, CosinePdf, HitRecord, Hittable,
+    HittablePdf, Material, MaterialRecord, MixturePdf, Pdf, Point3, Ray, Scene, LAMBDA_MAX,
+    LAMBDA_MIN,
+};
+
+/// Names accepted by `integrator_from_name`, the default first.
+pub const INTEGRATORS: &[&str] = &[
+    "path", "naive", "nee", "spectral", "direct", "ao", "normals", "albedo", "depth",
+];
+
+/// A light transport algorithm: estimates the radiance arriving along a
+/// camera ray.
+pub trait Integrator: Send + Sync {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color;
+}
+
+/// Returns one of the integrators listed in `INTEGRATORS`, with default
+/// parameters.
+pub fn integrator_from_name(name: &str) -> Option<Box<dyn Integrator>> {
+    match name {
+        "path" => Some(Box::new(PathTracer)),
+        "naive" => Some(Box::new(NaivePathTracer)),
+        "nee" => Some(Box::new(NeePathTracer)),
+        "spectral" => Some(Box::new(SpectralPathTracer)),
+        "direct" => Some(Box::new(DirectLighting)),
+        "ao" => Some(Box::new(AmbientOcclusion::default())),
+        "normals" => Some(Box::new(DebugIntegrator::Normals)),
+        "albedo" => Some(Box::new(DebugIntegrator::Albedo)),
+        "depth" => Some(Box::new(DebugIntegrator::Depth)),
+        _ => None,
+    }
+}
+
+/// Recursive path tracing that follows each material's own scattered ray.
+pub struct NaivePathTracer;
+
+impl NaivePathTracer {
+    fn trace(&self, ray: Ray, scene: &Scene, depth: i32) -> Color {
+        // If we've exceeded the ray bounce limit, gather no more light.
+        if depth <= 0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let mut rec = HitRecord::new();
+        if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+            return scene.background.color(&ray);
+        }
+        let Some(material) = rec.material.clone() else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+
+        let emitted = material.emitted(&ray, &rec);
+        let mat_rec = material.scatter(ray, rec);
+        if !mat_rec.scatter {
+            return emitted;
+        }
+        emitted + mat_rec.attenuation * self.trace(mat_rec.scattered.unwrap(), scene, depth - 1)
+    }
+}
+
+impl Integrator for NaivePathTracer {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        self.trace(ray, scene, scene.settings.max_depth)
+    }
+}
+
+/// Recursive path tracing that importance samples the material and, half of
+/// the time, the scene's lights at every diffuse bounce.
+pub struct PathTracer;
+
+impl PathTracer {
+    fn trace(&self, ray: Ray, scene: &Scene, depth: i32) -> Color {
+        if depth <= 0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+
+        let mut rec = HitRecord::new();
+        if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+            return scene.background.color(&ray);
+        }
+        let Some(material) = rec.material.clone() else {
+            return Color::new(0.0, 0.0, 0.0);
+        };
+
+        let emitted = material.emitted(&ray, &rec);
+        let p = rec.p.unwrap();
+        let mat_rec = material.scatter(ray, rec.clone());
+        if !mat_rec.scatter {
+            return emitted;
+        }
+
+        // Specular materials pick their own direction.
+        let Some(material_pdf) = &mat_rec.pdf else {
+            return emitted
+                + mat_rec.attenuation * self.trace(mat_rec.scattered.unwrap(), scene, depth - 1);
+        };
+
+        let (direction, pdf_value) = match scene.lights.as_deref() {
+            Some(lights) => {
+                let light_pdf = HittablePdf::new(lights, p);
+                let mixture = MixturePdf::new(&light_pdf, material_pdf.as_ref());
+                let direction = mixture.generate();
+                (direction, mixture.value(direction))
+            }
+            None => {
+                let direction = material_pdf.generate();
+                (direction, material_pdf.value(direction))
+            }
+        };
+        if pdf_value <= 0.0 {
+            return emitted;
+        }
+
+        let scattered = Ray::with_time(p, direction, ray.time());
+        let bsdf_cos = material.bsdf_cos(&ray, &rec, &mat_rec, &scattered);
+        emitted + bsdf_cos * self.trace(scattered, scene, depth - 1) / pdf_value
+    }
+}
+
+impl Integrator for PathTracer {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        self.trace(ray, scene, scene.settings.max_depth)
+    }
+}
+
+/// Path tracing with next event estimation: a shadow ray to a sampled light
+/// at every diffuse hit, combined with emission found by sampling the
+/// material using the power heuristic.
+pub struct NeePathTracer;
+
+impl Integrator for NeePathTracer {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let lights = scene.lights.as_deref();
+        let mut color = Color::new(0.0, 0.0, 0.0);
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+        // Pdf of the material sample that produced `ray`, or `None` if
+        // emission it finds can't also have been reached by sampling a light.
+        let mut material_pdf_value: Option<f64> = None;
+
+        for _ in 0..scene.settings.max_depth {
+            let mut rec = HitRecord::new();
+            if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+                color += throughput * scene.background.color(&ray);
+                break;
+            }
+            let Some(material) = rec.material.clone() else {
+                break;
+            };
+
+            let emitted = material.emitted(&ray, &rec);
+            if !emitted.near_zero() {
+                let weight = match (material_pdf_value, lights) {
+                    (Some(pdf), Some(lights)) => {
+                        power_heuristic(pdf, lights.pdf_value(ray.origin(), ray.direction()))
+                    }
+                    _ => 1.0,
+                };
+                color += throughput * emitted * weight;
+            }
+
+            let p = rec.p.unwrap();
+            let mat_rec = material.scatter(ray, rec.clone());
+            if !mat_rec.scatter {
+                break;
+            }
+            let Some(material_pdf) = &mat_rec.pdf else {
+                // Specular bounce: lights can't be sampled, so follow the ray.
+                throughput = throughput * mat_rec.attenuation;
+                ray = mat_rec.scattered.unwrap();
+                material_pdf_value = None;
+                continue;
+            };
+
+            if let Some(lights) = lights {
+                color += throughput
+                    * sample_light(scene, lights, &ray, &rec, &mat_rec, material.as_ref(), true);
+            }
+
+            // Material sample, continuing the path.
+            let direction = material_pdf.generate();
+            let pdf = material_pdf.value(direction);
+            if pdf <= 0.0 {
+                break;
+            }
+            let scattered = Ray::with_time(p, direction, ray.time());
+            throughput = throughput * material.bsdf_cos(&ray, &rec, &mat_rec, &scattered) / pdf;
+            ray = scattered;
+            material_pdf_value = Some(pdf);
+        }
+
+        color
+    }
+}
+
+/// Path tracing at one wavelength per camera ray, so dispersive dielectrics
+/// split white light. Otherwise like `PathTracer`, with RGB colors upsampled
+/// to spectra and the result converted back through CIE XYZ.
+pub struct SpectralPathTracer;
+
+impl SpectralPathTracer {
+    fn trace(&self, ray: Ray, scene: &Scene, depth: i32) -> f64 {
+        if depth <= 0 {
+            return 0.0;
+        }
+
+        let wavelength = ray.wavelength().unwrap();
+        let spectrum = |color: Color| rgb_to_spectrum(color, wavelength);
+        let mut rec = HitRecord::new();
+        if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+            return spectrum(scene.background.color(&ray));
+        }
+        let Some(material) = rec.material.clone() else {
+            return 0.0;
+        };
+
+        let emitted = spectrum(material.emitted(&ray, &rec));
+        let p = rec.p.unwrap();
+        let mat_rec = material.scatter(ray, rec.clone());
+        if !mat_rec.scatter {
+            return emitted;
+        }
+
+        // Materials make rays without a wavelength; keep the path's.
+        let Some(material_pdf) = &mat_rec.pdf else {
+            let scattered = mat_rec.scattered.unwrap().with_wavelength(wavelength);
+            let attenuation = spectrum(mat_rec.attenuation);
+            return emitted + attenuation * self.trace(scattered, scene, depth - 1);
+        };
+
+        let (direction, pdf_value) = match scene.lights.as_deref() {
+            Some(lights) => {
+                let light_pdf = HittablePdf::new(lights, p);
+                let mixture = MixturePdf::new(&light_pdf, material_pdf.as_ref());
+                let direction = mixture.generate();
+                (direction, mixture.value(direction))
+            }
+            None => {
+                let direction = material_pdf.generate();
+                (direction, material_pdf.value(direction))
+            }
+        };
+        if pdf_value <= 0.0 {
+            return emitted;
+        }
+
+        let scattered = Ray::with_time(p, direction, ray.time()).with_wavelength(wavelength);
+        let bsdf_cos = spectrum(material.bsdf_cos(&ray, &rec, &mat_rec, &scattered));
+        emitted + bsdf_cos * self.trace(scattered, scene, depth - 1) / pdf_value
+    }
+}
+
+impl Integrator for SpectralPathTracer {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let wavelength = LAMBDA_MIN + random() * (LAMBDA_MAX - LAMBDA_MIN);
+        let radiance = self.trace(
+            ray.with_wavelength(wavelength),
+            scene,
+            scene.settings.max_depth,
+        );
+        spectral_sample_to_rgb(wavelength, radiance, 1.0 / (LAMBDA_MAX - LAMBDA_MIN))
+    }
+}
+
+/// Direct lighting only: emission at the first diffuse hit plus one light
+/// sample, seen through any chain of specular bounces. Scenes without lights
+/// take one material sample towards the background instead.
+pub struct DirectLighting;
+
+impl Integrator for DirectLighting {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = ray;
+
+        for _ in 0..scene.settings.max_depth {
+            let mut rec = HitRecord::new();
+            if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+                return throughput * scene.background.color(&ray);
+            }
+            let Some(material) = rec.material.clone() else {
+                break;
+            };
+
+            let emitted = material.emitted(&ray, &rec);
+            let p = rec.p.unwrap();
+            let mat_rec = material.scatter(ray, rec.clone());
+            if !mat_rec.scatter {
+                return throughput * emitted;
+            }
+            let Some(material_pdf) = &mat_rec.pdf else {
+                throughput = throughput * mat_rec.attenuation;
+                ray = mat_rec.scattered.unwrap();
+                continue;
+            };
+
+            let direct = match scene.lights.as_deref() {
+                Some(lights) => sample_light(
+                    scene,
+                    lights,
+                    &ray,
+                    &rec,
+                    &mat_rec,
+                    material.as_ref(),
+                    false,
+                ),
+                None => {
+                    let direction = material_pdf.generate();
+                    let pdf = material_pdf.value(direction);
+                    if pdf <= 0.0 {
+                        Color::new(0.0, 0.0, 0.0)
+                    } else {
+                        let scattered = Ray::with_time(p, direction, ray.time());
+                        let mut light_rec = HitRecord::new();
+                        let light =
+                            if scene
+                                .world
+                                .hit(scattered, 0.001, f64::INFINITY, &mut light_rec)
+                            {
+                                light_rec
+                                    .material
+                                    .clone()
+                                    .map_or(Color::new(0.0, 0.0, 0.0), |m| {
+                                        m.emitted(&scattered, &light_rec)
+                                    })
+                            } else {
+                                scene.background.color(&scattered)
+                            };
+                        material.bsdf_cos(&ray, &rec, &mat_rec, &scattered) * light / pdf
+                    }
+                }
+            };
+            return throughput * (emitted + direct);
+        }
+
+        Color::new(0.0, 0.0, 0.0)
+    }
+}
+
+/// Ambient occlusion: the fraction of cosine-weighted directions above the
+/// first hit that escape within `distance`.
+pub struct AmbientOcclusion {
+    /// How far occluders count, or `None` for a tenth of the size of the
+    /// world's bounding box.
+    pub distance: Option<f64>,
+    pub samples: usize,
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        AmbientOcclusion {
+            distance: None,
+            samples: 1,
+        }
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut rec = HitRecord::new();
+        if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+            return Color::new(1.0, 1.0, 1.0);
+        }
+
+        let distance = self.distance.unwrap_or_else(|| {
+            scene.world.bounding_box().map_or(f64::INFINITY, |bbox| {
+                0.1 * (bbox.max() - bbox.min()).length()
+            })
+        });
+        let p = rec.p.unwrap();
+        let pdf = CosinePdf::new(rec.normal.unwrap());
+        let samples = self.samples.max(1);
+        let open = (0..samples)
+            .filter(|_| {
+                let direction = pdf.generate().unit_vector();
+                let occluder = Ray::with_time(p, direction, ray.time());
+                !scene
+                    .world
+                    .hit(occluder, 0.001, distance, &mut HitRecord::new())
+            })
+            .count();
+        let visibility = open as f64 / samples as f64;
+        Color::new(visibility, visibility, visibility)
+    }
+}
+
+/// Shows a property of the first surface hit instead of lighting.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DebugIntegrator {
+    /// The shading normal mapped from [-1, 1] to [0, 1].
+    Normals,
+    /// The material's attenuation, or its emission for lights.
+    Albedo,
+    /// The distance to the hit, unscaled so it survives in EXR output.
+    Depth,
+}
+
+impl Integrator for DebugIntegrator {
+    fn radiance(&self, ray: Ray, scene: &Scene) -> Color {
+        let mut rec = HitRecord::new();
+        if !scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec) {
+            return match self {
+                DebugIntegrator::Albedo => scene.background.color(&ray),
+                _ => Color::new(0.0, 0.0, 0.0),
+            };
+        }
+
+        match self {
+            DebugIntegrator::Normals => 0.5 * (rec.normal.unwrap() + Color::new(1.0, 1.0, 1.0)),
+            DebugIntegrator::Albedo => match rec.material.clone() {
+                Some(material) => {
+                    let emitted = material.emitted(&ray, &rec);
+                    let mat_rec = material.scatter(ray, rec);
+                    if mat_rec.scatter {
+                        mat_rec.attenuation
+                    } else {
+                        emitted
+                    }
+                }
+                None => Color::new(0.0, 0.0, 0.0),
+            },
+            DebugIntegrator::Depth => {
+                let depth = rec.t.unwrap() * ray.direction().length();
+                Color::new(depth, depth, depth)
+            }
+        }
+    }
+}
+
+/// Light reflected at `rec` from one point sampled on `lights`, checked with
+/// a shadow ray. With `mis`, the sample is weighted against the material's
+/// own pdf by the power heuristic.
+fn sample_light(
+    scene: &Scene,
+    lights: &dyn Hittable,
+    ray: &Ray,
+    rec: &HitRecord,
+    mat_rec: &MaterialRecord,
+    material: &dyn Material,
+    mis: bool,
+) -> Color {
+    let p: Point3 = rec.p.unwrap();
+    let direction = lights.random(p);
+    let light_pdf = lights.pdf_value(p, direction);
+    if light_pdf <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+
+    // Shadow rays look through media for the light, then attenuate by
+    // their estimated transmittance along the way.
+    let shadow_ray = Ray::with_time(p, direction, ray.time());
+    let mut light_rec = HitRecord::new();
+    if !scene
+        .world
+        .hit_surface(shadow_ray, 0.001, f64::INFINITY, &mut light_rec)
+    {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    let Some(light_material) = light_rec.material.clone() else {
+        return Color::new(0.0, 0.0, 0.0);
+    };
+
+    let light = light_material.emitted(&shadow_ray, &light_rec)
+        * scene
+            .world
+            .transmittance(shadow_ray, 0.001, light_rec.t.unwrap());
+    let f = material.bsdf_cos(ray, rec, mat_rec, &shadow_ray);
+    let weight = match (mis, &mat_rec.pdf) {
+        (true, Some(material_pdf)) => power_heuristic(light_pdf, material_pdf.value(direction)),
+        _ => 1.0,
+    };
+    f * light * weight / light_pdf
+}
+
+/// Weight for a sample drawn with density `pdf` when `other_pdf` could also
+/// have produced it (Veach's power heuristic with an exponent of two).
+pub fn power_heuristic(pdf: f64, other_pdf: f64) -> f64 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    if a + b == 0.0 {
+        0.0
+    } else {
+        a / (a + b)
+    }
+}
+
+#[test]
+fn integrators_agree_in_a_furnace() {
+    use std::sync::Arc;
+
+    use crate::{Background, Camera, Lambertian, RenderSettings, Sphere, Vec3};
+
+    // Light reflected off a convex diffuse object under a uniform white sky
+    // escapes straight back to the sky, so every estimate is the albedo.
+    let scene = Scene {
+        camera: Camera::new(
+            Point3::new(0.0, 0.0, 5.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            20.0,
+            1.0,
+            0.0,
+            5.0,
+        ),
+        world: Arc::new(Sphere::new(
+            Point3::new(0.0, 0.0, 0.0),
+            1.0,
+            Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+        )),
+        lights: None,
+        background: Background::Solid(Color::new(1.0, 1.0, 1.0)),
+        settings: RenderSettings::new(10, 10),
+    };
+    let ray = Ray::new(Point3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, -1.0));
+
+    for name in ["path", "naive", "nee", "direct"] {
+        let color = integrator_from_name(name).unwrap().radiance(ray, &scene);
+        assert!((color - Color::new(0.5, 0.5, 0.5)).near_zero(), "{}", name);
+    }
+
+    // One wavelength per sample, so only the average is grey.
+    crate::seed_thread_rng(Some(8));
+    let spectral = integrator_from_name("spectral").unwrap();
+    let samples = 200_000;
+    let mean = (0..samples)
+        .map(|_| spectral.radiance(ray, &scene))
+        .fold(Color::new(0.0, 0.0, 0.0), |sum, color| sum + color)
+        / samples as f64;
+    crate::seed_thread_rng(None);
+    assert!(
+        (mean - Color::new(0.5, 0.5, 0.5)).length() < 0.02,
+        "{:?}",
+        mean
+    );
+    let ao = integrator_from_name("ao").unwrap().radiance(ray, &scene);
+    assert_eq!(ao, Color::new(1.0, 1.0, 1.0));
+    let normal = integrator_from_name("normals")
+        .unwrap()
+        .radiance(ray, &scene);
+    assert_eq!(normal, Color::new(0.5, 0.5, 1.0));
+    let depth = integrator_from_name("depth").unwrap().radiance(ray, &scene);
+    assert_eq!(depth, Color::new(4.0, 4.0, 4.0));
+    assert!(integrator_from_name("bdpt").is_none());
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+
+use std::f64::consts::PI;
+
+use crate::{
+    random_in_unit_vector, Color, HitRecord, Material, Ray, SolidColor, SpherePdf, Texture,
+};
+
+/// Phase function scattering equally in every direction, for use inside
+/// participating media.
+pub struct Isotropic {
+    pub albedo: Arc<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(color: Color) -> Isotropic {
+        Isotropic::from_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Isotropic {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray: crate::Ray, rec: crate::HitRecord) -> crate::MaterialRecord {
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        crate::MaterialRecord {
+            attenuation: self.albedo.value(u, v, rec.p.unwrap()),
+            scattered: Some(Ray::with_time(
+                rec.p.unwrap(),
+                random_in_unit_vector(),
+                ray.time(),
+            )),
+            scatter: true,
+            pdf: Some(Box::new(SpherePdf)),
+        }
+    }
+
+    fn scattering_pdf(&self, _ray: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+}
@@ -1,17 +1,25 @@
-use crate::{random_in_unit_vector, Color, Material, Ray};
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    random_in_unit_vector, Color, CosinePdf, HitRecord, Material, Ray, SolidColor, Texture,
+};
 
 pub struct Lambertian {
-    pub albedo: Color,
+    pub albedo: Arc<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(color: Color) -> Lambertian {
-        Lambertian { albedo: color }
+        Lambertian::from_texture(Arc::new(SolidColor::new(color)))
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>) -> Lambertian {
+        Lambertian { albedo }
     }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: crate::Ray, rec: crate::HitRecord) -> crate::MaterialRecord {
+    fn scatter(&self, ray: crate::Ray, rec: crate::HitRecord) -> crate::MaterialRecord {
         let mut scatter_direction = rec.normal.unwrap() + random_in_unit_vector();
 
         // Catch degenerate scatter direction
@@ -19,10 +27,21 @@ impl Material for Lambertian {
             scatter_direction = rec.normal.unwrap();
         }
 
-        return crate::MaterialRecord {
-            attenuation: self.albedo,
-            scattered: Some(Ray::new(rec.p.unwrap(), scatter_direction)),
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        crate::MaterialRecord {
+            attenuation: self.albedo.value(u, v, rec.p.unwrap()),
+            scattered: Some(Ray::with_time(
+                rec.p.unwrap(),
+                scatter_direction,
+                ray.time(),
+            )),
             scatter: true,
-        };
+            pdf: Some(Box::new(CosinePdf::new(rec.normal.unwrap()))),
+        }
+    }
+
+    fn scattering_pdf(&self, _ray: &Ray, rec: &HitRecord, scattered: &Ray) -> f64 {
+        let cosine = rec.normal.unwrap().dot(scattered.direction().unit_vector());
+        (cosine / PI).max(0.0)
     }
 }
@@ -1,25 +1,105 @@
+mod aa_rect;
+mod aabb;
+mod background;
+mod bvh;
 mod camera;
+mod conductor;
+mod constant_medium;
+mod cuboid;
+mod density_grid;
 mod dielectric;
+mod diffuse_light;
+mod film;
+mod hdr;
+mod henyey_greenstein;
+mod heterogeneous_medium;
 mod hittable;
 mod hittable_list;
+mod instance;
+mod integrator;
+mod isotropic;
 mod lambertian;
 mod material;
 mod math;
+mod matrix;
 mod metal;
+mod microfacet;
+mod moving_sphere;
+mod obj;
+mod onb;
+mod pdf;
+mod perlin;
+mod principled;
+mod quad;
 mod ray;
+mod render;
+mod rough_conductor;
+mod rough_dielectric;
+mod scene;
+mod scenes;
+mod spectrum;
 mod sphere;
+mod texture;
+mod tone_map;
+mod triangle;
+mod triangle_mesh;
 mod vec3;
 
+pub use crate::aa_rect::{AaRect, Plane};
+pub use crate::aabb::{surrounding_box, Aabb};
+pub use crate::background::Background;
+pub use crate::bvh::{BvhNode, SplitHeuristic};
 pub use crate::camera::Camera;
-pub use crate::dielectric::Dielectric;
+pub use crate::conductor::{Conductor, CONDUCTOR_PRESETS};
+pub use crate::constant_medium::ConstantMedium;
+pub use crate::cuboid::Cuboid;
+pub use crate::density_grid::DensityGrid;
+pub use crate::dielectric::{Dielectric, Dispersion};
+pub use crate::diffuse_light::DiffuseLight;
+pub use crate::film::{Film, ImageFormat};
+pub use crate::henyey_greenstein::HenyeyGreenstein;
+pub use crate::heterogeneous_medium::HeterogeneousMedium;
 pub use crate::hittable::{HitRecord, Hittable};
 pub use crate::hittable_list::HittableList;
+pub use crate::instance::Instance;
+pub use crate::integrator::{
+    integrator_from_name, power_heuristic, AmbientOcclusion, DebugIntegrator, DirectLighting,
+    Integrator, NaivePathTracer, NeePathTracer, PathTracer, SpectralPathTracer, INTEGRATORS,
+};
+pub use crate::isotropic::Isotropic;
 pub use crate::lambertian::Lambertian;
 pub use crate::material::{Material, MaterialRecord};
-pub use crate::math::{random, random_in_range};
+pub use crate::math::{random, random_in_range, seed_thread_rng};
+pub use crate::matrix::Matrix4;
 pub use crate::metal::Metal;
+pub use crate::microfacet::{
+    fresnel_conductor, fresnel_dielectric, fresnel_schlick, Ggx, MicrofacetPdf,
+};
+pub use crate::moving_sphere::MovingSphere;
+pub use crate::obj::{
+    load_obj, parse_mtl, parse_obj, MaterialLibrary, ObjError, ObjGroup, ObjModel,
+};
+pub use crate::onb::Onb;
+pub use crate::pdf::{random_cosine_direction, CosinePdf, HittablePdf, MixturePdf, Pdf, SpherePdf};
+pub use crate::perlin::Perlin;
+pub use crate::principled::Principled;
+pub use crate::quad::Quad;
 pub use crate::ray::Ray;
+pub use crate::render::{render, Crop, RenderSettings};
+pub use crate::rough_conductor::RoughConductor;
+pub use crate::rough_dielectric::RoughDielectric;
+pub use crate::scene::{load_scene, parse_scene, Scene, SceneError};
+pub use crate::scenes::{builtin_scene, random_scene, BUILTIN_SCENES};
+pub use crate::spectrum::{
+    cie_xyz, rgb_to_spectrum, spectral_sample_to_rgb, xyz_to_linear_srgb, LAMBDA_MAX, LAMBDA_MIN,
+};
 pub use crate::sphere::Sphere;
+pub use crate::texture::{
+    CheckerTexture, ImageTexture, MarbleTexture, NoiseTexture, SolidColor, Texture, WoodTexture,
+};
+pub use crate::tone_map::{luminance, ToneMap, ToneMapOperator, TransferFunction};
+pub use crate::triangle::Triangle;
+pub use crate::triangle_mesh::{MeshData, MeshTriangle, TriangleMesh};
 pub use crate::vec3::{
     random as vec3_random, random_in_hemisphere, random_in_range as vec3_random_in_range,
     random_in_unit_sphere, random_in_unit_vector, Color, Point3, Vec3,
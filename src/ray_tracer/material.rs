@@ -1,11 +1,53 @@
-use crate::{Color, HitRecord, Ray};
+use crate::{Color, HitRecord, Pdf, Ray};
 
 pub struct MaterialRecord {
     pub attenuation: Color,
     pub scattered: Option<Ray>,
     pub scatter: bool,
+    /// Distribution of scattered directions for importance sampling, or
+    /// `None` for specular materials whose `scattered` ray is used as is.
+    pub pdf: Option<Box<dyn Pdf>>,
 }
 
-pub trait Material {
+impl MaterialRecord {
+    /// All light is absorbed and nothing scatters.
+    pub fn absorbed() -> MaterialRecord {
+        MaterialRecord {
+            attenuation: Color::new(0.0, 0.0, 0.0),
+            scattered: None,
+            scatter: false,
+            pdf: None,
+        }
+    }
+}
+
+pub trait Material: Send + Sync {
     fn scatter(&self, ray: Ray, rec: HitRecord) -> MaterialRecord;
+
+    /// Density, per steradian, with which the material scatters light
+    /// from `ray` into `scattered`. Only used for materials with a `pdf`.
+    fn scattering_pdf(&self, _ray: &Ray, _rec: &HitRecord, _scattered: &Ray) -> f64 {
+        0.0
+    }
+
+    /// The BSDF times the cosine to the normal for light arriving along
+    /// `scattered` and leaving along `ray`, with `mat_rec` from `scatter`.
+    /// Only used for materials with a `pdf`.
+    ///
+    /// The default spreads the attenuation by `scattering_pdf`; materials
+    /// whose color changes with direction override this instead.
+    fn bsdf_cos(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        mat_rec: &MaterialRecord,
+        scattered: &Ray,
+    ) -> Color {
+        mat_rec.attenuation * self.scattering_pdf(ray, rec, scattered)
+    }
+
+    /// Radiance emitted from the hit point towards the ray origin.
+    fn emitted(&self, _ray: &Ray, _rec: &HitRecord) -> Color {
+        Color::new(0.0, 0.0, 0.0)
+    }
 }
@@ -1,12 +1,37 @@
-use rand::Rng;
+use std::cell::RefCell;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+thread_local! {
+    static SEEDED_RNG: RefCell<Option<StdRng>> = const { RefCell::new(None) };
+}
+
+/// Seeds the random number generator of the calling thread.
+///
+/// Passing `None` goes back to the default, non-reproducible generator.
+pub fn seed_thread_rng(seed: Option<u64>) {
+    SEEDED_RNG.with(|rng| *rng.borrow_mut() = seed.map(StdRng::seed_from_u64));
+}
 
 /// Generates a random number between 0 and 1.
 pub fn random() -> f64 {
-    let mut rng = rand::thread_rng();
-    rng.gen::<f64>()
+    SEEDED_RNG.with(|rng| match rng.borrow_mut().as_mut() {
+        Some(rng) => rng.gen::<f64>(),
+        None => rand::thread_rng().gen::<f64>(),
+    })
 }
 
 /// Generates a random number within a specified range.
 pub fn random_in_range(min: f64, max: f64) -> f64 {
     min + (max - min) * random()
 }
+
+#[test]
+fn seeded_random_is_reproducible() {
+    seed_thread_rng(Some(42));
+    let first: Vec<f64> = (0..4).map(|_| random()).collect();
+    seed_thread_rng(Some(42));
+    let second: Vec<f64> = (0..4).map(|_| random()).collect();
+    seed_thread_rng(None);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,179 @@
+use std::ops;
+
+use crate::{Point3, Vec3};
+
+/// A 4x4 row-major matrix of an affine transform.
+///
+/// Composes right to left: `b * a` applies `a` first.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Matrix4 {
+    pub m: [[f64; 4]; 4],
+}
+
+impl Matrix4 {
+    pub fn identity() -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        Matrix4 { m }
+    }
+
+    pub fn translation(offset: Vec3) -> Matrix4 {
+        let mut matrix = Matrix4::identity();
+        for i in 0..3 {
+            matrix.m[i][3] = offset[i];
+        }
+        matrix
+    }
+
+    pub fn scaling(factors: Vec3) -> Matrix4 {
+        let mut matrix = Matrix4::identity();
+        for i in 0..3 {
+            matrix.m[i][i] = factors[i];
+        }
+        matrix
+    }
+
+    /// Counter-clockwise rotation by `degrees` about `axis`, looking down
+    /// the axis toward the origin.
+    pub fn rotation(axis: Vec3, degrees: f64) -> Matrix4 {
+        let a = axis.unit_vector();
+        let (x, y, z) = (a.x(), a.y(), a.z());
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        let t = 1.0 - cos;
+        Matrix4 {
+            m: [
+                [
+                    t * x * x + cos,
+                    t * x * y - sin * z,
+                    t * x * z + sin * y,
+                    0.0,
+                ],
+                [
+                    t * x * y + sin * z,
+                    t * y * y + cos,
+                    t * y * z - sin * x,
+                    0.0,
+                ],
+                [
+                    t * x * z - sin * y,
+                    t * y * z + sin * x,
+                    t * z * z + cos,
+                    0.0,
+                ],
+                [0.0, 0.0, 0.0, 1.0],
+            ],
+        }
+    }
+
+    pub fn transpose(&self) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = self.m[j][i];
+            }
+        }
+        Matrix4 { m }
+    }
+
+    /// Determinant of the upper-left 3x3 block, the linear part of the
+    /// transform, which scales volumes by its absolute value.
+    pub fn linear_determinant(&self) -> f64 {
+        let m = &self.m;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    /// Gauss-Jordan elimination with partial pivoting, or `None` if the
+    /// matrix is singular.
+    pub fn inverse(&self) -> Option<Matrix4> {
+        let mut a = self.m;
+        let mut inv = Matrix4::identity().m;
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))
+                .unwrap();
+            if a[pivot][col].abs() < 1e-12 {
+                return None;
+            }
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = 1.0 / a[col][col];
+            for j in 0..4 {
+                a[col][j] *= scale;
+                inv[col][j] *= scale;
+            }
+            for row in 0..4 {
+                let factor = a[row][col];
+                if row != col && factor != 0.0 {
+                    for j in 0..4 {
+                        a[row][j] -= factor * a[col][j];
+                        inv[row][j] -= factor * inv[col][j];
+                    }
+                }
+            }
+        }
+        Some(Matrix4 { m: inv })
+    }
+
+    pub fn transform_point(&self, p: Point3) -> Point3 {
+        let m = &self.m;
+        Point3::new(
+            m[0][0] * p.x() + m[0][1] * p.y() + m[0][2] * p.z() + m[0][3],
+            m[1][0] * p.x() + m[1][1] * p.y() + m[1][2] * p.z() + m[1][3],
+            m[2][0] * p.x() + m[2][1] * p.y() + m[2][2] * p.z() + m[2][3],
+        )
+    }
+
+    /// Transforms a direction, ignoring the translation.
+    pub fn transform_vector(&self, v: Vec3) -> Vec3 {
+        let m = &self.m;
+        Vec3::new(
+            m[0][0] * v.x() + m[0][1] * v.y() + m[0][2] * v.z(),
+            m[1][0] * v.x() + m[1][1] * v.y() + m[1][2] * v.z(),
+            m[2][0] * v.x() + m[2][1] * v.y() + m[2][2] * v.z(),
+        )
+    }
+}
+
+impl Default for Matrix4 {
+    fn default() -> Self {
+        Matrix4::identity()
+    }
+}
+
+impl ops::Mul<Matrix4> for Matrix4 {
+    type Output = Matrix4;
+
+    fn mul(self, rhs: Matrix4) -> Matrix4 {
+        let mut m = [[0.0; 4]; 4];
+        for (i, row) in m.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| self.m[i][k] * rhs.m[k][j]).sum();
+            }
+        }
+        Matrix4 { m }
+    }
+}
+
+#[test]
+fn can_invert_matrix() {
+    let transform = Matrix4::translation(Vec3::new(1.0, -2.0, 3.0))
+        * Matrix4::rotation(Vec3::new(1.0, 1.0, 0.0), 30.0)
+        * Matrix4::scaling(Vec3::new(2.0, 0.5, -1.0));
+    let product = transform * transform.inverse().unwrap();
+    for i in 0..4 {
+        for j in 0..4 {
+            let expected = if i == j { 1.0 } else { 0.0 };
+            assert!((product.m[i][j] - expected).abs() < 1e-12);
+        }
+    }
+    assert_eq!(Matrix4::scaling(Vec3::new(1.0, 0.0, 1.0)).inverse(), None);
+
+    let p = Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), 90.0)
+        .transform_point(Point3::new(1.0, 0.0, 0.0));
+    assert!((p - Point3::new(0.0, 0.0, -1.0)).length() < 1e-12);
+}
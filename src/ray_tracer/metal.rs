@@ -1,35 +1,41 @@
-use crate::{random_in_unit_sphere, Color, Material, Ray};
+use std::sync::Arc;
+
+use crate::{random_in_unit_sphere, Color, Material, Ray, SolidColor, Texture};
 
 pub struct Metal {
-    pub albedo: Color,
+    pub albedo: Arc<dyn Texture>,
     pub fuzz: f64,
 }
 
 impl Metal {
     pub fn new(color: Color, fuzz: f64) -> Metal {
+        Metal::from_texture(Arc::new(SolidColor::new(color)), fuzz)
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>, fuzz: f64) -> Metal {
         let mut f = 1.0;
         if fuzz < 1.0 {
             f = fuzz;
         }
-        Metal {
-            albedo: color,
-            fuzz: f,
-        }
+        Metal { albedo, fuzz: f }
     }
 }
 
 impl Material for Metal {
     fn scatter(&self, ray: crate::Ray, rec: crate::HitRecord) -> crate::MaterialRecord {
         let reflected = ray.direction().unit_vector().reflect(rec.normal.unwrap());
-        let scattered = Ray::new(
+        let scattered = Ray::with_time(
             rec.p.unwrap(),
             reflected + self.fuzz * random_in_unit_sphere(),
+            ray.time(),
         );
 
-        return crate::MaterialRecord {
-            attenuation: self.albedo,
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        crate::MaterialRecord {
+            attenuation: self.albedo.value(u, v, rec.p.unwrap()),
             scattered: Some(scattered),
+            pdf: None,
             scatter: scattered.direction().dot(rec.normal.unwrap()) > 0.0,
-        };
+        }
     }
 }
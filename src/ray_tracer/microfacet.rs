@@ -0,0 +1,314 @@
+use std::f64::consts::PI;
+
+use crate::{random, vec3::cross, Color, Onb, Pdf, Vec3};
+
+/// The GGX (Trowbridge-Reitz) distribution of microfacet normals, with
+/// height-correlated Smith shadowing.
+///
+/// Directions are in a local frame where the macro surface normal is +Z.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Ggx {
+    pub alpha: f64,
+}
+
+impl Ggx {
+    /// Uses the common `alpha = roughness²` remapping, so `roughness` is
+    /// perceptually linear. Roughness 0 is kept just above a perfect mirror.
+    pub fn new(roughness: f64) -> Ggx {
+        let roughness = roughness.clamp(0.0, 1.0);
+        Ggx {
+            alpha: (roughness * roughness).max(1e-3),
+        }
+    }
+
+    /// Density of microfacet normals `h`, per unit projected area.
+    pub fn d(&self, h: Vec3) -> f64 {
+        if h.z() <= 0.0 {
+            return 0.0;
+        }
+        let a2 = self.alpha * self.alpha;
+        let cos2 = h.z() * h.z();
+        let denom = cos2 * (a2 - 1.0) + 1.0;
+        a2 / (PI * denom * denom)
+    }
+
+    fn lambda(&self, w: Vec3) -> f64 {
+        let cos2 = w.z() * w.z();
+        if cos2 == 0.0 {
+            return f64::INFINITY;
+        }
+        let tan2 = (w.x() * w.x() + w.y() * w.y()) / cos2;
+        0.5 * ((1.0 + self.alpha * self.alpha * tan2).sqrt() - 1.0)
+    }
+
+    /// Fraction of microfacets visible from `w`.
+    pub fn g1(&self, w: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// Fraction of microfacets visible from both `wo` and `wi`.
+    pub fn g2(&self, wo: Vec3, wi: Vec3) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Samples a microfacet normal as seen from `wo` (Heitz 2018), with
+    /// density `g1(wo) * max(0, wo · h) * d(h) / wo.z`.
+    pub fn sample_visible_normal(&self, wo: Vec3) -> Vec3 {
+        // Stretch the view so the distribution becomes a hemisphere.
+        let vh = Vec3::new(self.alpha * wo.x(), self.alpha * wo.y(), wo.z()).unit_vector();
+        let len2 = vh.x() * vh.x() + vh.y() * vh.y();
+        let t1 = if len2 > 0.0 {
+            Vec3::new(-vh.y(), vh.x(), 0.0) / len2.sqrt()
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let t2 = cross(&vh, &t1);
+
+        // Sample the projected half disk.
+        let r = random().sqrt();
+        let phi = 2.0 * PI * random();
+        let p1 = r * phi.cos();
+        let s = 0.5 * (1.0 + vh.z());
+        let p2 = (1.0 - s) * (1.0 - p1 * p1).sqrt() + s * r * phi.sin();
+        let nh = p1 * t1 + p2 * t2 + (1.0 - p1 * p1 - p2 * p2).max(0.0).sqrt() * vh;
+
+        // Unstretch.
+        Vec3::new(self.alpha * nh.x(), self.alpha * nh.y(), nh.z().max(0.0)).unit_vector()
+    }
+
+    /// Density of reflecting `wo` into `wi` off a normal drawn by
+    /// `sample_visible_normal`: the visible normal density times the
+    /// Jacobian `1 / (4 wo · h)` of the reflection.
+    pub fn reflection_pdf(&self, wo: Vec3, wi: Vec3) -> f64 {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return 0.0;
+        }
+        let h = (wo + wi).unit_vector();
+        self.g1(wo) * self.d(h) / (4.0 * wo.z())
+    }
+
+    /// The BRDF times the cosine for reflecting `wo` into `wi`, where
+    /// `fresnel` gives the reflectance for the cosine between `wo` and the
+    /// microfacet normal.
+    pub fn reflection(&self, wo: Vec3, wi: Vec3, fresnel: impl Fn(f64) -> Color) -> Color {
+        if wo.z() <= 0.0 || wi.z() <= 0.0 {
+            return Color::new(0.0, 0.0, 0.0);
+        }
+        let h = (wo + wi).unit_vector();
+        fresnel(wo.dot(h)) * (self.d(h) * self.g2(wo, wi) / (4.0 * wo.z()))
+    }
+}
+
+/// Reflects `wo` off a visible microfacet sampled from `ggx`, returning the
+/// local direction and its weight `F * G2 / G1`, where `fresnel` gives the
+/// reflectance for the cosine between `wo` and the microfacet normal.
+///
+/// Reflections into the surface are absorbed, with a weight of zero.
+pub fn reflect(ggx: Ggx, wo: Vec3, fresnel: impl Fn(f64) -> Color) -> (Vec3, Color) {
+    let h = ggx.sample_visible_normal(wo);
+    let wi = (-wo).reflect(h);
+    if wi.z() <= 0.0 {
+        return (wi, Color::new(0.0, 0.0, 0.0));
+    }
+    let weight = ggx.g2(wo, wi) / ggx.g1(wo);
+    (wi, weight * fresnel(wo.dot(h)))
+}
+
+/// Directions scattered off the GGX microfacets visible from `wo`, in the
+/// frame `uvw`.
+///
+/// Conductors only reflect. With `eta`, the ratio of the indices of
+/// refraction on the incident and transmitted sides, dielectrics reflect or
+/// refract in proportion to the Fresnel reflectance.
+pub struct MicrofacetPdf {
+    pub uvw: Onb,
+    pub wo: Vec3,
+    pub distribution: Ggx,
+    pub eta: Option<f64>,
+}
+
+impl MicrofacetPdf {
+    /// For light leaving against `direction`, the incoming ray's, from a
+    /// surface with `normal`.
+    pub fn new(
+        distribution: Ggx,
+        normal: Vec3,
+        direction: Vec3,
+        eta: Option<f64>,
+    ) -> MicrofacetPdf {
+        let uvw = Onb::from_w(normal);
+        MicrofacetPdf {
+            uvw,
+            wo: uvw.to_local(-direction.unit_vector()),
+            distribution,
+            eta,
+        }
+    }
+}
+
+impl Pdf for MicrofacetPdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let (ggx, wo) = (self.distribution, self.wo);
+        let wi = self.uvw.to_local(direction.unit_vector());
+        match self.eta {
+            None => ggx.reflection_pdf(wo, wi),
+            Some(eta) if wi.z() > 0.0 => {
+                let h = (wo + wi).unit_vector();
+                fresnel_dielectric(wo.dot(h), eta) * ggx.reflection_pdf(wo, wi)
+            }
+            Some(eta) => {
+                // The normal that refracts wo into wi, facing wo.
+                let h = -(eta * wo + wi);
+                if wo.z() <= 0.0 || h.length_squared() == 0.0 {
+                    return 0.0;
+                }
+                let h = if h.z() < 0.0 { -h } else { h }.unit_vector();
+                let (cos_o, cos_i) = (wo.dot(h), wi.dot(h));
+                if cos_o <= 0.0 || cos_i >= 0.0 {
+                    return 0.0;
+                }
+                let visible = ggx.g1(wo) * cos_o * ggx.d(h) / wo.z();
+                let jacobian = -cos_i / (eta * cos_o + cos_i).powi(2);
+                (1.0 - fresnel_dielectric(cos_o, eta)) * visible * jacobian
+            }
+        }
+    }
+
+    /// Samples that would end on the wrong side of the surface become a
+    /// tangent direction, which scatters nothing, so `value` stays the
+    /// density of every direction that does.
+    fn generate(&self) -> Vec3 {
+        let wo = self.wo;
+        let h = self.distribution.sample_visible_normal(wo);
+        let wi = match self.eta {
+            Some(eta) if random() >= fresnel_dielectric(wo.dot(h), eta) => {
+                let wi = (-wo).refract(h, eta);
+                if wi.z() >= 0.0 {
+                    return self.uvw.u;
+                }
+                wi
+            }
+            _ => {
+                let wi = (-wo).reflect(h);
+                if wi.z() <= 0.0 && self.eta.is_some() {
+                    return self.uvw.u;
+                }
+                wi
+            }
+        };
+        self.uvw.local(wi)
+    }
+}
+
+/// Schlick's approximation for a surface with normal-incidence reflectance
+/// `f0`.
+pub fn fresnel_schlick(cosine: f64, f0: Color) -> Color {
+    f0 + (1.0 - cosine).clamp(0.0, 1.0).powi(5) * (Color::new(1.0, 1.0, 1.0) - f0)
+}
+
+/// Unpolarized reflectance of a dielectric interface, where `eta` is the
+/// ratio of the indices of refraction on the incident and transmitted sides.
+pub fn fresnel_dielectric(cos_i: f64, eta: f64) -> f64 {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let sin2_t = eta * eta * (1.0 - cos_i * cos_i);
+    if sin2_t >= 1.0 {
+        // Total internal reflection.
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+    let r_s = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let r_p = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    0.5 * (r_s * r_s + r_p * r_p)
+}
+
+/// Unpolarized reflectance of a conductor with complex index of refraction
+/// `eta + i k`, per color channel.
+pub fn fresnel_conductor(cos_i: f64, eta: Color, k: Color) -> Color {
+    let cos_i = cos_i.clamp(0.0, 1.0);
+    let channel = |eta: f64, k: f64| {
+        let cos2 = cos_i * cos_i;
+        let sin2 = 1.0 - cos2;
+        let t0 = eta * eta - k * k - sin2;
+        let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+        let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+        let t1 = a2_plus_b2 + cos2;
+        let t2 = 2.0 * cos_i * a;
+        let r_s = (t1 - t2) / (t1 + t2);
+        let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+        let t4 = t2 * sin2;
+        let r_p = r_s * (t3 - t4) / (t3 + t4);
+        0.5 * (r_s + r_p)
+    };
+    Color::new(
+        channel(eta.x(), k.x()),
+        channel(eta.y(), k.y()),
+        channel(eta.z(), k.z()),
+    )
+}
+
+#[test]
+fn ggx_visible_normals_match_the_distribution() {
+    crate::seed_thread_rng(Some(4));
+    let ggx = Ggx::new(0.7);
+    let normal = Vec3::new(0.0, 0.0, 1.0);
+
+    // Seen head on, visible normals have density d(h) * h.z, so the mean of
+    // 1 / d(h) is the integral of h.z over the hemisphere.
+    let samples = 200_000;
+    let estimate = (0..samples)
+        .map(|_| 1.0 / ggx.d(ggx.sample_visible_normal(normal)))
+        .sum::<f64>()
+        / samples as f64;
+    crate::seed_thread_rng(None);
+    assert!((estimate - PI).abs() < 0.03, "{}", estimate);
+
+    assert_eq!(ggx.g1(normal), 1.0);
+    assert!((fresnel_dielectric(1.0, 1.0 / 1.5) - 0.04).abs() < 1e-12);
+    assert_eq!(fresnel_dielectric(0.1, 1.5), 1.0);
+
+    // At normal incidence a conductor reflects ((n-1)² + k²) / ((n+1)² + k²),
+    // and everything at grazing incidence.
+    let (eta, k) = (Color::new(0.2, 1.0, 1.5), Color::new(3.9, 2.4, 0.0));
+    let head_on = fresnel_conductor(1.0, eta, k);
+    let expected = |n: f64, k: f64| ((n - 1.0).powi(2) + k * k) / ((n + 1.0).powi(2) + k * k);
+    assert!((head_on.x() - expected(0.2, 3.9)).abs() < 1e-12);
+    assert!((head_on.z() - expected(1.5, 0.0)).abs() < 1e-12);
+    assert!((fresnel_conductor(0.0, eta, k) - Color::new(1.0, 1.0, 1.0)).near_zero());
+}
+
+#[test]
+fn microfacet_pdfs_match_their_samples() {
+    crate::seed_thread_rng(Some(6));
+    let normal = Vec3::new(0.0, 0.0, 1.0);
+    let incoming = Vec3::new(0.6, 0.0, -0.8);
+    let samples = 400_000;
+    // The pdf integrates to the fraction of samples that aren't discarded
+    // for leaving on the wrong side of the surface. Integrate over uniform
+    // directions on the sphere, so the mean of 4π · pdf is the integral.
+    let check = |pdf: &MicrofacetPdf| {
+        let kept = (0..samples)
+            .filter(|_| pdf.value(pdf.generate()) > 0.0)
+            .count() as f64
+            / samples as f64;
+        let integral = (0..samples)
+            .map(|_| 4.0 * PI * pdf.value(crate::random_in_unit_vector()))
+            .sum::<f64>()
+            / samples as f64;
+        assert!(kept > 0.9, "{}", kept);
+        assert!((integral - kept).abs() < 0.02, "{} vs {}", integral, kept);
+    };
+    check(&MicrofacetPdf::new(Ggx::new(0.5), normal, incoming, None));
+    check(&MicrofacetPdf::new(
+        Ggx::new(0.5),
+        normal,
+        incoming,
+        Some(1.0 / 1.5),
+    ));
+    check(&MicrofacetPdf::new(
+        Ggx::new(0.5),
+        normal,
+        incoming,
+        Some(1.5),
+    ));
+    crate::seed_thread_rng(None);
+}
@@ -0,0 +1,94 @@
+use std::sync::Arc;
+
+use crate::{
+    sphere::hit_sphere, surrounding_box, Aabb, HitRecord, Hittable, Material, Point3, Ray, Vec3,
+};
+
+/// A sphere moving in a straight line from `center0` at `time0` to `center1`
+/// at `time1`.
+pub struct MovingSphere {
+    pub center0: Point3,
+    pub center1: Point3,
+    pub time0: f64,
+    pub time1: f64,
+    pub radius: f64,
+    pub material: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    pub fn new(
+        center0: Point3,
+        center1: Point3,
+        time0: f64,
+        time1: f64,
+        radius: f64,
+        material: Arc<dyn Material>,
+    ) -> MovingSphere {
+        MovingSphere {
+            center0,
+            center1,
+            time0,
+            time1,
+            radius,
+            material,
+        }
+    }
+
+    /// Returns the center at `time`, extrapolating outside the keyframes.
+    pub fn center(&self, time: f64) -> Point3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        let t = (time - self.time0) / (self.time1 - self.time0);
+        self.center0 + t * (self.center1 - self.center0)
+    }
+}
+
+impl Hittable for MovingSphere {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        hit_sphere(
+            self.center(ray.time()),
+            self.radius,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+            rec,
+        )
+    }
+
+    /// Bounds the sphere over the whole interval between the two keyframes.
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(surrounding_box(
+            &Aabb::new(self.center0 - r, self.center0 + r),
+            &Aabb::new(self.center1 - r, self.center1 + r),
+        ))
+    }
+}
+
+#[test]
+fn moving_sphere_follows_ray_time() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let sphere = MovingSphere::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+        0.0,
+        1.0,
+        0.5,
+        material,
+    );
+    assert_eq!(sphere.center(0.5), Point3::new(1.0, 0.0, 0.0));
+
+    let down = Vec3::new(0.0, 0.0, -1.0);
+    let mut rec = HitRecord::new();
+    let early = Ray::with_time(Point3::new(2.0, 0.0, 5.0), down, 0.0);
+    assert!(!sphere.hit(early, 0.001, f64::INFINITY, &mut rec));
+    let late = Ray::with_time(Point3::new(2.0, 0.0, 5.0), down, 1.0);
+    assert!(sphere.hit(late, 0.001, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(4.5));
+
+    let bbox = sphere.bounding_box().unwrap();
+    assert_eq!(bbox.min(), Point3::new(-0.5, -0.5, -0.5));
+    assert_eq!(bbox.max(), Point3::new(2.5, 0.5, 0.5));
+}
@@ -0,0 +1,620 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use crate::{
+    vec3::cross, Color, Dielectric, Hittable, HittableList, Lambertian, Material, MeshData, Metal,
+    Point3, TriangleMesh, Vec3,
+};
+
+/// Materials declared in an MTL file, by name.
+pub type MaterialLibrary = HashMap<String, Arc<dyn Material>>;
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ObjError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ObjError::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+        }
+    }
+}
+
+impl Error for ObjError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ObjError::Io { source, .. } => Some(source),
+            ObjError::Parse { .. } => None,
+        }
+    }
+}
+
+/// A group of faces sharing one material.
+pub struct ObjGroup {
+    pub name: String,
+    pub material_name: Option<String>,
+    pub mesh: TriangleMesh,
+}
+
+pub struct ObjModel {
+    pub groups: Vec<ObjGroup>,
+}
+
+impl ObjModel {
+    /// Returns every triangle of the model, ready to go into a `BvhNode`.
+    pub fn triangles(&self) -> Vec<Arc<dyn Hittable>> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.mesh.triangles())
+            .collect()
+    }
+
+    /// Returns one hittable per group, each with its own BVH.
+    pub fn into_list(self) -> HittableList<dyn Hittable> {
+        HittableList {
+            objects: self
+                .groups
+                .into_iter()
+                .map(|group| Arc::new(group.mesh) as Arc<dyn Hittable>)
+                .collect(),
+        }
+    }
+}
+
+/// Loads an OBJ file along with any MTL libraries it references.
+///
+/// Faces without a material use `default_material`.
+pub fn load_obj(
+    path: impl AsRef<Path>,
+    default_material: Arc<dyn Material>,
+) -> Result<ObjModel, ObjError> {
+    let path = path.as_ref();
+    let source = read_file(path)?;
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_obj(
+        &source,
+        &path.display().to_string(),
+        default_material,
+        |name| {
+            let mtl_path = dir.join(name);
+            parse_mtl(&read_file(&mtl_path)?, &mtl_path.display().to_string())
+        },
+    )
+}
+
+/// Parses OBJ source text. `load_mtl` resolves `mtllib` statements.
+pub fn parse_obj<F>(
+    source: &str,
+    file: &str,
+    default_material: Arc<dyn Material>,
+    mut load_mtl: F,
+) -> Result<ObjModel, ObjError>
+where
+    F: FnMut(&str) -> Result<MaterialLibrary, ObjError>,
+{
+    let mut positions: Vec<Point3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<(f64, f64)> = Vec::new();
+    let mut materials = MaterialLibrary::new();
+
+    let mut builders: Vec<GroupBuilder> = Vec::new();
+    let mut group_name = String::from("default");
+    let mut material_name: Option<String> = None;
+    let mut current: Option<usize> = None;
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let err = |message: String| ObjError::Parse {
+            file: file.to_string(),
+            line: line_number,
+            message,
+        };
+
+        let line = raw_line.split('#').next().unwrap().trim();
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let v = parse_floats(&args, 3, 4).map_err(err)?;
+                positions.push(Point3::new(v[0], v[1], v[2]));
+            }
+            "vn" => {
+                let v = parse_floats(&args, 3, 3).map_err(err)?;
+                normals.push(Vec3::new(v[0], v[1], v[2]));
+            }
+            "vt" => {
+                let v = parse_floats(&args, 1, 3).map_err(err)?;
+                uvs.push((v[0], v.get(1).copied().unwrap_or(0.0)));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(err(format!(
+                        "face needs at least 3 vertices, found {}",
+                        args.len()
+                    )));
+                }
+                let vertices = args
+                    .iter()
+                    .map(|arg| parse_face_vertex(arg, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(err)?;
+
+                let builder = match current {
+                    Some(i) => i,
+                    None => {
+                        builders.push(GroupBuilder::new(&group_name, material_name.clone()));
+                        builders.len() - 1
+                    }
+                };
+                current = Some(builder);
+
+                let polygon: Vec<Point3> = vertices.iter().map(|v| positions[v.0]).collect();
+                for [a, b, c] in triangulate(&polygon) {
+                    let face = [
+                        builders[builder].vertex(vertices[a]),
+                        builders[builder].vertex(vertices[b]),
+                        builders[builder].vertex(vertices[c]),
+                    ];
+                    builders[builder].indices.push(face);
+                }
+            }
+            "g" | "o" => {
+                group_name = if args.is_empty() {
+                    String::from("default")
+                } else {
+                    args.join(" ")
+                };
+                current = None;
+            }
+            "usemtl" => {
+                let name = args
+                    .first()
+                    .ok_or_else(|| err(String::from("usemtl needs a material name")))?;
+                if !materials.contains_key(*name) {
+                    return Err(err(format!("unknown material '{}'", name)));
+                }
+                material_name = Some(name.to_string());
+                current = None;
+            }
+            "mtllib" => {
+                if args.is_empty() {
+                    return Err(err(String::from("mtllib needs a file name")));
+                }
+                for name in args {
+                    materials.extend(load_mtl(name)?);
+                }
+            }
+            // Smoothing groups, lines, points and other statements don't
+            // affect rendering.
+            _ => {}
+        }
+    }
+
+    let groups: Vec<ObjGroup> = builders
+        .into_iter()
+        .filter(|builder| !builder.indices.is_empty())
+        .map(|builder| {
+            let material = builder
+                .material_name
+                .as_ref()
+                .map_or_else(|| default_material.clone(), |name| materials[name].clone());
+            builder.build(material, &positions, &uvs, &normals)
+        })
+        .collect();
+
+    if groups.is_empty() {
+        return Err(ObjError::Parse {
+            file: file.to_string(),
+            line: source.lines().count(),
+            message: String::from("file contains no faces"),
+        });
+    }
+
+    Ok(ObjModel { groups })
+}
+
+/// Parses MTL source text, mapping each material onto the closest crate material.
+///
+/// Transparent materials (`d` < 1, or a refraction `illum` model) become
+/// `Dielectric` with index `Ni`, reflective ones (`illum 3`, or black `Kd`
+/// with a non-black `Ks`) become `Metal` tinted by `Ks` with fuzz derived
+/// from `Ns`, and everything else becomes `Lambertian` with albedo `Kd`.
+pub fn parse_mtl(source: &str, file: &str) -> Result<MaterialLibrary, ObjError> {
+    let mut library = MaterialLibrary::new();
+    let mut current: Option<MtlDefinition> = None;
+
+    for (index, raw_line) in source.lines().enumerate() {
+        let line_number = index + 1;
+        let err = |message: String| ObjError::Parse {
+            file: file.to_string(),
+            line: line_number,
+            message,
+        };
+
+        let line = raw_line.split('#').next().unwrap().trim();
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            let name = args
+                .first()
+                .ok_or_else(|| err(String::from("newmtl needs a material name")))?;
+            if let Some(definition) = current.take() {
+                library.insert(definition.name.clone(), definition.into_material());
+            }
+            current = Some(MtlDefinition::new(name));
+            continue;
+        }
+
+        let definition = match current.as_mut() {
+            Some(definition) => definition,
+            None if matches!(keyword, "Kd" | "Ks" | "Ni" | "Ns" | "d" | "Tr" | "illum") => {
+                return Err(err(format!("'{}' before any newmtl", keyword)));
+            }
+            None => continue,
+        };
+
+        match keyword {
+            "Kd" => definition.kd = parse_color(&args).map_err(err)?,
+            "Ks" => definition.ks = parse_color(&args).map_err(err)?,
+            "Ni" => definition.ni = parse_floats(&args, 1, 1).map_err(err)?[0],
+            "Ns" => definition.ns = parse_floats(&args, 1, 1).map_err(err)?[0],
+            "d" => definition.d = parse_floats(&args, 1, 1).map_err(err)?[0],
+            "Tr" => definition.d = 1.0 - parse_floats(&args, 1, 1).map_err(err)?[0],
+            "illum" => {
+                definition.illum = args
+                    .first()
+                    .and_then(|arg| arg.parse().ok())
+                    .ok_or_else(|| err(String::from("illum needs an integer model")))?;
+            }
+            // Texture maps and other statements are not supported yet.
+            _ => {}
+        }
+    }
+
+    if let Some(definition) = current.take() {
+        library.insert(definition.name.clone(), definition.into_material());
+    }
+
+    Ok(library)
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn parse_floats(args: &[&str], min: usize, max: usize) -> Result<Vec<f64>, String> {
+    if args.len() < min || args.len() > max {
+        return Err(if min == max {
+            format!("expected {} numbers, found {}", min, args.len())
+        } else {
+            format!("expected {} to {} numbers, found {}", min, max, args.len())
+        });
+    }
+    args.iter()
+        .map(|arg| {
+            arg.parse::<f64>()
+                .map_err(|_| format!("invalid number '{}'", arg))
+        })
+        .collect()
+}
+
+fn parse_color(args: &[&str]) -> Result<Color, String> {
+    // A single value is shorthand for a grey.
+    let v = parse_floats(args, 1, 3)?;
+    match v.len() {
+        1 => Ok(Color::new(v[0], v[0], v[0])),
+        3 => Ok(Color::new(v[0], v[1], v[2])),
+        n => Err(format!("expected 1 or 3 color components, found {}", n)),
+    }
+}
+
+/// Position, texture coordinate and normal indices of a face vertex.
+type FaceVertex = (usize, Option<usize>, Option<usize>);
+
+fn parse_face_vertex(
+    arg: &str,
+    position_count: usize,
+    uv_count: usize,
+    normal_count: usize,
+) -> Result<FaceVertex, String> {
+    let mut parts = arg.split('/');
+    let position = resolve_index(parts.next().unwrap_or(""), position_count, "vertex")?;
+    let uv = match parts.next() {
+        Some("") | None => None,
+        Some(part) => Some(resolve_index(part, uv_count, "texture coordinate")?),
+    };
+    let normal = match parts.next() {
+        Some("") | None => None,
+        Some(part) => Some(resolve_index(part, normal_count, "normal")?),
+    };
+    if parts.next().is_some() {
+        return Err(format!("invalid face vertex '{}'", arg));
+    }
+    Ok((position, uv, normal))
+}
+
+/// Converts a 1-based (or negative, relative) OBJ index to a 0-based one.
+fn resolve_index(part: &str, count: usize, kind: &str) -> Result<usize, String> {
+    let index: i64 = part
+        .parse()
+        .map_err(|_| format!("invalid {} index '{}'", kind, part))?;
+    let resolved = if index > 0 {
+        index - 1
+    } else {
+        count as i64 + index
+    };
+    if index == 0 || resolved < 0 || resolved >= count as i64 {
+        return Err(format!(
+            "{} index {} out of range ({} defined)",
+            kind, index, count
+        ));
+    }
+    Ok(resolved as usize)
+}
+
+/// Splits a planar polygon into triangles by ear clipping, so concave faces
+/// are handled. Falls back to a fan if the polygon is degenerate.
+fn triangulate(polygon: &[Point3]) -> Vec<[usize; 3]> {
+    let n = polygon.len();
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let fan = || (1..n - 1).map(|i| [0, i, i + 1]).collect();
+
+    // Newell's method gives a robust normal for the polygon.
+    let mut normal = Vec3::new(0.0, 0.0, 0.0);
+    for i in 0..n {
+        let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+        normal += Vec3::new(
+            (a.y() - b.y()) * (a.z() + b.z()),
+            (a.z() - b.z()) * (a.x() + b.x()),
+            (a.x() - b.x()) * (a.y() + b.y()),
+        );
+    }
+    if normal.near_zero() {
+        return fan();
+    }
+
+    let is_convex = |a: Point3, b: Point3, c: Point3| cross(&(b - a), &(c - b)).dot(normal) > 0.0;
+    let contains = |a: Point3, b: Point3, c: Point3, p: Point3| {
+        cross(&(b - a), &(p - a)).dot(normal) >= 0.0
+            && cross(&(c - b), &(p - b)).dot(normal) >= 0.0
+            && cross(&(a - c), &(p - c)).dot(normal) >= 0.0
+    };
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|&i| {
+            let (ia, ib, ic) = (
+                remaining[(i + m - 1) % m],
+                remaining[i],
+                remaining[(i + 1) % m],
+            );
+            let (a, b, c) = (polygon[ia], polygon[ib], polygon[ic]);
+            is_convex(a, b, c)
+                && remaining
+                    .iter()
+                    .filter(|&&j| j != ia && j != ib && j != ic)
+                    .all(|&j| !contains(a, b, c, polygon[j]))
+        });
+        let i = match ear {
+            Some(i) => i,
+            None => return fan(),
+        };
+        triangles.push([
+            remaining[(i + m - 1) % m],
+            remaining[i],
+            remaining[(i + 1) % m],
+        ]);
+        remaining.remove(i);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+/// Collects the de-duplicated vertices and faces of one group.
+struct GroupBuilder {
+    name: String,
+    material_name: Option<String>,
+    vertex_map: HashMap<FaceVertex, usize>,
+    vertices: Vec<FaceVertex>,
+    indices: Vec<[usize; 3]>,
+}
+
+impl GroupBuilder {
+    fn new(name: &str, material_name: Option<String>) -> GroupBuilder {
+        GroupBuilder {
+            name: name.to_string(),
+            material_name,
+            vertex_map: HashMap::new(),
+            vertices: Vec::new(),
+            indices: Vec::new(),
+        }
+    }
+
+    fn vertex(&mut self, vertex: FaceVertex) -> usize {
+        let next = self.vertices.len();
+        *self.vertex_map.entry(vertex).or_insert_with(|| {
+            self.vertices.push(vertex);
+            next
+        })
+    }
+
+    fn build(
+        self,
+        material: Arc<dyn Material>,
+        positions: &[Point3],
+        uvs: &[(f64, f64)],
+        normals: &[Vec3],
+    ) -> ObjGroup {
+        // Attributes are only kept if every vertex in the group has them.
+        let group_uvs = if self.vertices.iter().all(|v| v.1.is_some()) {
+            self.vertices.iter().map(|v| uvs[v.1.unwrap()]).collect()
+        } else {
+            Vec::new()
+        };
+        let group_normals = if self.vertices.iter().all(|v| v.2.is_some()) {
+            self.vertices
+                .iter()
+                .map(|v| normals[v.2.unwrap()])
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        ObjGroup {
+            name: self.name,
+            material_name: self.material_name,
+            mesh: TriangleMesh::new(MeshData {
+                positions: self.vertices.iter().map(|v| positions[v.0]).collect(),
+                normals: group_normals,
+                uvs: group_uvs,
+                indices: self.indices,
+                material,
+            }),
+        }
+    }
+}
+
+/// Raw MTL statements for one material.
+struct MtlDefinition {
+    name: String,
+    kd: Color,
+    ks: Color,
+    ni: f64,
+    ns: f64,
+    d: f64,
+    illum: u32,
+}
+
+impl MtlDefinition {
+    fn new(name: &str) -> MtlDefinition {
+        MtlDefinition {
+            name: name.to_string(),
+            kd: Color::new(0.8, 0.8, 0.8),
+            ks: Color::new(0.0, 0.0, 0.0),
+            ni: 1.5,
+            ns: 0.0,
+            d: 1.0,
+            illum: 2,
+        }
+    }
+
+    fn into_material(self) -> Arc<dyn Material> {
+        let transparent = self.d < 1.0 || matches!(self.illum, 4 | 6 | 7 | 9);
+        let reflective = self.illum == 3 || (self.kd.near_zero() && !self.ks.near_zero());
+        if transparent {
+            Arc::new(Dielectric::new(self.ni))
+        } else if reflective {
+            // Map the Phong exponent onto a roughness-like fuzz in [0, 1].
+            let fuzz = (2.0 / (self.ns.max(0.0) + 2.0)).sqrt();
+            Arc::new(Metal::new(self.ks, fuzz))
+        } else {
+            Arc::new(Lambertian::new(self.kd))
+        }
+    }
+}
+
+#[test]
+fn can_parse_obj_with_materials() {
+    let mtl = "newmtl red\nKd 0.8 0.1 0.1\n\nnewmtl glass\nNi 1.45\nd 0.1\n";
+    let obj = "mtllib scene.mtl\n\
+               v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+               vt 0 0\nvt 1 0\nvt 1 1\nvt 0 1\n\
+               g floor\nusemtl red\nf 1/1 2/2 3/3 4/4\n\
+               g pane\nusemtl glass\nf -4 -3 -2\n";
+    let default_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let model = parse_obj(obj, "scene.obj", default_material, |name| {
+        assert_eq!(name, "scene.mtl");
+        parse_mtl(mtl, name)
+    })
+    .unwrap();
+
+    assert_eq!(model.groups.len(), 2);
+    assert_eq!(model.groups[0].name, "floor");
+    assert_eq!(model.groups[0].material_name.as_deref(), Some("red"));
+    assert_eq!(model.groups[0].mesh.data().indices.len(), 2);
+    assert_eq!(model.groups[0].mesh.data().uvs.len(), 4);
+    assert_eq!(model.groups[1].mesh.data().indices.len(), 1);
+    assert!(model.groups[1].mesh.data().uvs.is_empty());
+    assert_eq!(model.triangles().len(), 3);
+}
+
+#[test]
+fn obj_errors_report_line_numbers() {
+    let default_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let obj = "v 0 0 0\nv 1 0 0\nv 1 1 oops\nf 1 2 3\n";
+    let err = parse_obj(obj, "bad.obj", default_material.clone(), |_| {
+        Ok(MaterialLibrary::new())
+    })
+    .err()
+    .unwrap();
+    assert_eq!(err.to_string(), "bad.obj:3: invalid number 'oops'");
+
+    let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nf 1 2 4\n";
+    let err = parse_obj(obj, "bad.obj", default_material, |_| {
+        Ok(MaterialLibrary::new())
+    })
+    .err()
+    .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "bad.obj:4: vertex index 4 out of range (3 defined)"
+    );
+}
+
+#[test]
+fn can_triangulate_concave_polygon() {
+    // An L shape whose first vertex is reflex, so a fan would be wrong.
+    let polygon = [
+        Point3::new(1.0, 1.0, 0.0),
+        Point3::new(1.0, 2.0, 0.0),
+        Point3::new(0.0, 2.0, 0.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(2.0, 1.0, 0.0),
+    ];
+    let triangles = triangulate(&polygon);
+    assert_eq!(triangles.len(), 4);
+    let area: f64 = triangles
+        .iter()
+        .map(|&[a, b, c]| 0.5 * cross(&(polygon[b] - polygon[a]), &(polygon[c] - polygon[a])).z())
+        .sum();
+    assert!((area - 3.0).abs() < 1e-9);
+}
@@ -0,0 +1,35 @@
+use crate::{vec3::cross, Vec3};
+
+/// Orthonormal basis, used to turn directions sampled around the Z axis
+/// into directions around an arbitrary `w`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Onb {
+    pub u: Vec3,
+    pub v: Vec3,
+    pub w: Vec3,
+}
+
+impl Onb {
+    pub fn from_w(n: Vec3) -> Onb {
+        let w = n.unit_vector();
+        // Any vector not parallel to w will do.
+        let a = if w.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let v = cross(&w, &a).unit_vector();
+        let u = cross(&w, &v);
+        Onb { u, v, w }
+    }
+
+    /// Converts coordinates in this basis to world space.
+    pub fn local(&self, a: Vec3) -> Vec3 {
+        a.x() * self.u + a.y() * self.v + a.z() * self.w
+    }
+
+    /// Converts a world space vector to coordinates in this basis.
+    pub fn to_local(&self, a: Vec3) -> Vec3 {
+        Vec3::new(a.dot(self.u), a.dot(self.v), a.dot(self.w))
+    }
+}
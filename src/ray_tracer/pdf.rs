@@ -0,0 +1,125 @@
+use std::f64::consts::PI;
+
+use crate::{random, Hittable, Onb, Point3, Vec3};
+
+/// A distribution of directions that can be both sampled and evaluated,
+/// with densities measured per steradian.
+pub trait Pdf: Send + Sync {
+    fn value(&self, direction: Vec3) -> f64;
+
+    fn generate(&self) -> Vec3;
+}
+
+/// Directions weighted by their cosine to a surface normal, matching a
+/// Lambertian reflector.
+pub struct CosinePdf {
+    uvw: Onb,
+}
+
+impl CosinePdf {
+    pub fn new(normal: Vec3) -> CosinePdf {
+        CosinePdf {
+            uvw: Onb::from_w(normal),
+        }
+    }
+}
+
+impl Pdf for CosinePdf {
+    fn value(&self, direction: Vec3) -> f64 {
+        let cosine = direction.unit_vector().dot(self.uvw.w);
+        (cosine / PI).max(0.0)
+    }
+
+    fn generate(&self) -> Vec3 {
+        self.uvw.local(random_cosine_direction())
+    }
+}
+
+/// Directions uniformly distributed over the whole sphere.
+pub struct SpherePdf;
+
+impl Pdf for SpherePdf {
+    fn value(&self, _direction: Vec3) -> f64 {
+        1.0 / (4.0 * PI)
+    }
+
+    fn generate(&self) -> Vec3 {
+        crate::random_in_unit_vector()
+    }
+}
+
+/// Directions from `origin` toward the surface of `objects`, such as the
+/// lights of a scene.
+pub struct HittablePdf<'a> {
+    pub objects: &'a dyn Hittable,
+    pub origin: Point3,
+}
+
+impl<'a> HittablePdf<'a> {
+    pub fn new(objects: &'a dyn Hittable, origin: Point3) -> HittablePdf<'a> {
+        HittablePdf { objects, origin }
+    }
+}
+
+impl Pdf for HittablePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        self.objects.pdf_value(self.origin, direction)
+    }
+
+    fn generate(&self) -> Vec3 {
+        self.objects.random(self.origin)
+    }
+}
+
+/// An even blend of two distributions.
+pub struct MixturePdf<'a> {
+    pub pdfs: [&'a dyn Pdf; 2],
+}
+
+impl<'a> MixturePdf<'a> {
+    pub fn new(a: &'a dyn Pdf, b: &'a dyn Pdf) -> MixturePdf<'a> {
+        MixturePdf { pdfs: [a, b] }
+    }
+}
+
+impl Pdf for MixturePdf<'_> {
+    fn value(&self, direction: Vec3) -> f64 {
+        0.5 * self.pdfs[0].value(direction) + 0.5 * self.pdfs[1].value(direction)
+    }
+
+    fn generate(&self) -> Vec3 {
+        if random() < 0.5 {
+            self.pdfs[0].generate()
+        } else {
+            self.pdfs[1].generate()
+        }
+    }
+}
+
+/// Samples the hemisphere around +Z with density cos(theta) / pi.
+pub fn random_cosine_direction() -> Vec3 {
+    let r1 = random();
+    let r2 = random();
+    let phi = 2.0 * PI * r1;
+    let z = (1.0 - r2).sqrt();
+    Vec3::new(phi.cos() * r2.sqrt(), phi.sin() * r2.sqrt(), z)
+}
+
+#[test]
+fn cosine_pdf_matches_its_samples() {
+    crate::seed_thread_rng(Some(2));
+    let normal = Vec3::new(0.0, 1.0, 0.0);
+    let pdf = CosinePdf::new(normal);
+    // Monte Carlo estimate of the integral of cos^2 over the hemisphere.
+    let samples = 20_000;
+    let estimate = (0..samples)
+        .map(|_| {
+            let direction = pdf.generate();
+            direction.dot(normal).powi(2) / pdf.value(direction)
+        })
+        .sum::<f64>()
+        / samples as f64;
+    crate::seed_thread_rng(None);
+    assert!((estimate - 2.0 * PI / 3.0).abs() < 0.02, "{}", estimate);
+    assert_eq!(pdf.value(Vec3::new(0.0, -1.0, 0.0)), 0.0);
+}
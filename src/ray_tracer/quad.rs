@@ -0,0 +1,167 @@
+use std::sync::Arc;
+
+use crate::{
+    random, surrounding_box, triangle::triangle_box, vec3::cross, Aabb, HitRecord, Hittable,
+    Material, Point3, Ray, Vec3,
+};
+
+/// A parallelogram with corner `q` and edges `u` and `v`.
+///
+/// Texture coordinates run from 0 to 1 along each edge.
+pub struct Quad {
+    pub q: Point3,
+    pub u: Vec3,
+    pub v: Vec3,
+    pub material: Arc<dyn Material>,
+    normal: Vec3,
+    /// Plane offset, such that `normal · p = d` on the quad.
+    d: f64,
+    /// Projects a point in the plane onto the edges: n / (n · n).
+    w: Vec3,
+}
+
+impl Quad {
+    /// # Panics
+    ///
+    /// If `u` and `v` are parallel, or either is zero, so they span no plane.
+    pub fn new(q: Point3, u: Vec3, v: Vec3, material: Arc<dyn Material>) -> Quad {
+        let n = cross(&u, &v);
+        assert!(
+            n.length_squared() > 0.0,
+            "quad edges {:?} and {:?} span no plane",
+            u,
+            v
+        );
+        let normal = n.unit_vector();
+        Quad {
+            q,
+            u,
+            v,
+            material,
+            normal,
+            d: normal.dot(q),
+            w: n / n.dot(n),
+        }
+    }
+}
+
+impl Hittable for Quad {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let denom = self.normal.dot(ray.direction());
+        if denom.abs() < 1e-12 {
+            // The ray is parallel to the plane.
+            return false;
+        }
+
+        let t = (self.d - self.normal.dot(ray.origin())) / denom;
+        if t < t_min || t_max < t {
+            return false;
+        }
+
+        let p = ray.at(t);
+        let planar = p - self.q;
+        let alpha = self.w.dot(cross(&planar, &self.v));
+        let beta = self.w.dot(cross(&self.u, &planar));
+        if !(0.0..=1.0).contains(&alpha) || !(0.0..=1.0).contains(&beta) {
+            return false;
+        }
+
+        rec.t = Some(t);
+        rec.p = Some(p);
+        rec.set_face_normal(&ray, self.normal);
+        rec.material = Some(self.material.clone());
+        rec.barycentric = None;
+        rec.uv = Some((alpha, beta));
+
+        true
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let (q, u, v) = (self.q, self.u, self.v);
+        Some(surrounding_box(
+            &triangle_box(q, q + u, q + v),
+            &triangle_box(q + u, q + v, q + u + v),
+        ))
+    }
+
+    /// Uniform over the quad's area, converted to solid angle.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(Ray::new(origin, direction), 0.001, f64::INFINITY, &mut rec) {
+            return 0.0;
+        }
+
+        let t = rec.t.unwrap();
+        let distance_squared = t * t * direction.length_squared();
+        let cosine = (direction.dot(self.normal) / direction.length()).abs();
+        let area = cross(&self.u, &self.v).length();
+        distance_squared / (cosine * area)
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        self.q + random() * self.u + random() * self.v - origin
+    }
+}
+
+#[test]
+fn can_hit_quad() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let quad = Quad::new(
+        Point3::new(-1.0, -1.0, 0.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Vec3::new(0.0, 4.0, 0.0),
+        material,
+    );
+    let mut rec = HitRecord::new();
+    let ray = Ray::new(Point3::new(0.5, 0.0, 2.0), Vec3::new(0.0, 0.0, -1.0));
+    assert!(quad.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(2.0));
+    assert_eq!(rec.uv, Some((0.75, 0.25)));
+    assert_eq!(rec.normal, Some(Vec3::new(0.0, 0.0, 1.0)));
+
+    let miss = Ray::new(Point3::new(1.5, 0.0, 2.0), Vec3::new(0.0, 0.0, -1.0));
+    assert!(!quad.hit(miss, 0.001, f64::INFINITY, &mut rec));
+
+    // The box is padded so the flat quad can still be hit through a BVH.
+    let bbox = quad.bounding_box().unwrap();
+    assert!(bbox.hit(ray, 0.001, f64::INFINITY));
+}
+
+#[test]
+#[should_panic(expected = "span no plane")]
+fn rejects_parallel_quad_edges() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    Quad::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(-2.0, 0.0, 0.0),
+        material,
+    );
+}
+
+#[test]
+fn quad_pdf_integrates_to_one() {
+    use std::f64::consts::PI;
+
+    crate::seed_thread_rng(Some(3));
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let quad = Quad::new(
+        Point3::new(-1.0, 1.0, -1.0),
+        Vec3::new(2.0, 0.0, 0.0),
+        Vec3::new(0.0, 0.0, 2.0),
+        material,
+    );
+    let origin = Point3::new(0.0, 0.0, 0.0);
+
+    // Integrate over uniformly chosen directions; the pdf must sum to one.
+    let samples = 200_000;
+    let estimate = (0..samples)
+        .map(|_| 4.0 * PI * quad.pdf_value(origin, crate::random_in_unit_vector()))
+        .sum::<f64>()
+        / samples as f64;
+    let direction = quad.random(origin);
+    crate::seed_thread_rng(None);
+    assert!((estimate - 1.0).abs() < 0.02, "{}", estimate);
+    assert!(quad.pdf_value(origin, direction) > 0.0);
+    assert_eq!(quad.pdf_value(origin, Vec3::new(0.0, -1.0, 0.0)), 0.0);
+}
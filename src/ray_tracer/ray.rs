@@ -4,6 +4,10 @@ use crate::vec3::{Point3, Vec3};
 pub struct Ray {
     orig: Point3,
     dir: Vec3,
+    /// Moment within the camera's shutter interval the ray was fired at.
+    time: f64,
+    /// Wavelength in nanometers carried by spectral rendering, else `None`.
+    wavelength: Option<f64>,
 }
 
 impl Ray {
@@ -11,15 +15,42 @@ impl Ray {
         Ray {
             orig: origin,
             dir: direction,
+            time: 0.0,
+            wavelength: None,
+        }
+    }
+
+    pub fn with_time(origin: Point3, direction: Vec3, time: f64) -> Ray {
+        Ray {
+            orig: origin,
+            dir: direction,
+            time,
+            wavelength: None,
+        }
+    }
+
+    /// The same ray, carrying light of a single `wavelength` in nanometers.
+    pub fn with_wavelength(self, wavelength: f64) -> Ray {
+        Ray {
+            wavelength: Some(wavelength),
+            ..self
         }
     }
 
     pub fn origin(&self) -> Point3 {
-        self.orig.clone()
+        self.orig
     }
 
     pub fn direction(&self) -> Vec3 {
-        self.dir.clone()
+        self.dir
+    }
+
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    pub fn wavelength(&self) -> Option<f64> {
+        self.wavelength
     }
 
     pub fn at(&self, t: f64) -> Point3 {
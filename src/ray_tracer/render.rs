@@ -0,0 +1,183 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::{seed_thread_rng, Color, Film};
+
+/// Image and sampling parameters for a render.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct RenderSettings {
+    pub image_width: usize,
+    pub image_height: usize,
+    pub samples_per_pixel: usize,
+    pub max_depth: i32,
+    /// Number of worker threads, or 0 to use every available core.
+    pub threads: usize,
+    /// Seed for reproducible renders, independent of the thread count.
+    pub seed: Option<u64>,
+    /// Region of the image to render, or `None` for the whole image.
+    pub crop: Option<Crop>,
+}
+
+impl RenderSettings {
+    pub fn new(image_width: usize, image_height: usize) -> RenderSettings {
+        RenderSettings {
+            image_width,
+            image_height,
+            samples_per_pixel: 100,
+            max_depth: 50,
+            threads: 0,
+            seed: None,
+            crop: None,
+        }
+    }
+
+    pub fn aspect_ratio(&self) -> f64 {
+        self.image_width as f64 / self.image_height as f64
+    }
+
+    /// Returns the region that will be rendered.
+    pub fn window(&self) -> Crop {
+        self.crop.unwrap_or(Crop {
+            x0: 0,
+            y0: 0,
+            x1: self.image_width,
+            y1: self.image_height,
+        })
+    }
+}
+
+/// A pixel rectangle measured from the top-left corner of the image, with
+/// exclusive upper bounds.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Crop {
+    pub x0: usize,
+    pub y0: usize,
+    pub x1: usize,
+    pub y1: usize,
+}
+
+impl Crop {
+    pub fn width(&self) -> usize {
+        self.x1 - self.x0
+    }
+
+    pub fn height(&self) -> usize {
+        self.y1 - self.y0
+    }
+}
+
+/// Renders an image one scanline at a time across worker threads.
+///
+/// `pixel` is called with the column and row of each pixel, where row 0 is the
+/// bottom of the image, and returns the sum of `samples_per_pixel` samples.
+/// `progress` is called with the number of scanlines remaining each time
+/// one finishes. The returned film covers the render window.
+pub fn render<F, P>(settings: &RenderSettings, pixel: F, progress: P) -> Film
+where
+    F: Fn(usize, usize) -> Color + Sync,
+    P: Fn(usize) + Sync,
+{
+    let threads = if settings.threads == 0 {
+        thread::available_parallelism().map_or(1, |n| n.get())
+    } else {
+        settings.threads
+    };
+    let window = settings.window();
+    let lines = window.height();
+
+    let next_scanline = AtomicUsize::new(0);
+    let scanlines_done = AtomicUsize::new(0);
+    let scanlines = Mutex::new(vec![Vec::new(); lines]);
+
+    thread::scope(|scope| {
+        for _ in 0..threads.min(lines.max(1)) {
+            scope.spawn(|| loop {
+                let line = next_scanline.fetch_add(1, Ordering::Relaxed);
+                if line >= lines {
+                    break;
+                }
+
+                let y = window.y0 + line;
+                if let Some(seed) = settings.seed {
+                    // Seed per scanline so the image doesn't depend on scheduling.
+                    seed_thread_rng(Some(seed ^ (y as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)));
+                }
+
+                let j = settings.image_height - 1 - y;
+                let colors: Vec<Color> = (window.x0..window.x1).map(|i| pixel(i, j)).collect();
+                scanlines.lock().unwrap()[line] = colors;
+
+                let done = scanlines_done.fetch_add(1, Ordering::Relaxed) + 1;
+                progress(lines - done);
+            });
+        }
+    });
+
+    let mut film = Film::new(window.width(), lines);
+    for (y, colors) in scanlines.into_inner().unwrap().into_iter().enumerate() {
+        for (x, color) in colors.into_iter().enumerate() {
+            film.add_samples(x, y, color, settings.samples_per_pixel);
+        }
+    }
+    film
+}
+
+#[test]
+fn render_returns_pixels_in_scanline_order() {
+    let mut settings = RenderSettings::new(7, 5);
+    settings.samples_per_pixel = 1;
+    settings.threads = 3;
+    let remaining = Mutex::new(Vec::new());
+    let film = render(
+        &settings,
+        |i, j| Color::new(i as f64, j as f64, 0.0),
+        |left| remaining.lock().unwrap().push(left),
+    );
+    let mut remaining = remaining.into_inner().unwrap();
+    remaining.sort_unstable();
+    assert_eq!(remaining, [0, 1, 2, 3, 4]);
+    assert_eq!((film.width(), film.height()), (7, 5));
+    assert_eq!(film.pixel(0, 0), Color::new(0.0, 4.0, 0.0));
+    assert_eq!(film.pixel(6, 0), Color::new(6.0, 4.0, 0.0));
+    assert_eq!(film.pixel(6, 4), Color::new(6.0, 0.0, 0.0));
+
+    settings.crop = Some(Crop {
+        x0: 2,
+        y0: 1,
+        x1: 4,
+        y1: 3,
+    });
+    let film = render(
+        &settings,
+        |i, j| Color::new(i as f64, j as f64, 0.0),
+        |_| {},
+    );
+    assert_eq!((film.width(), film.height()), (2, 2));
+    assert_eq!(film.pixel(0, 0), Color::new(2.0, 3.0, 0.0));
+    assert_eq!(film.pixel(1, 1), Color::new(3.0, 2.0, 0.0));
+}
+
+#[test]
+fn seeded_render_ignores_thread_count() {
+    let mut settings = RenderSettings::new(4, 6);
+    settings.seed = Some(7);
+    settings.threads = 1;
+    let serial = render(
+        &settings,
+        |_, _| Color::new(crate::random(), 0.0, 0.0),
+        |_| {},
+    );
+    settings.threads = 3;
+    let parallel = render(
+        &settings,
+        |_, _| Color::new(crate::random(), 0.0, 0.0),
+        |_| {},
+    );
+    let tone_map = crate::ToneMap::default();
+    assert_eq!(serial.to_rgb8(&tone_map), parallel.to_rgb8(&tone_map));
+}
@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use crate::{
+    microfacet::{fresnel_schlick, reflect},
+    Color, Ggx, HitRecord, Material, MaterialRecord, MicrofacetPdf, Onb, Ray, SolidColor, Texture,
+};
+
+/// A rough metal with a GGX microfacet BRDF.
+///
+/// `albedo` is the reflectance at normal incidence, brightening to white at
+/// grazing angles. Reflections are sampled from the visible normals, so each
+/// scattered ray carries the weight `F * G2 / G1`, and the same sampling is
+/// offered as a `pdf` so lights can be sampled too.
+pub struct RoughConductor {
+    pub albedo: Arc<dyn Texture>,
+    pub distribution: Ggx,
+}
+
+impl RoughConductor {
+    pub fn new(color: Color, roughness: f64) -> RoughConductor {
+        RoughConductor::from_texture(Arc::new(SolidColor::new(color)), roughness)
+    }
+
+    pub fn from_texture(albedo: Arc<dyn Texture>, roughness: f64) -> RoughConductor {
+        RoughConductor {
+            albedo,
+            distribution: Ggx::new(roughness),
+        }
+    }
+}
+
+impl Material for RoughConductor {
+    fn scatter(&self, ray: Ray, rec: HitRecord) -> MaterialRecord {
+        let uvw = Onb::from_w(rec.normal.unwrap());
+        let wo = uvw.to_local(-ray.direction().unit_vector());
+        if wo.z() <= 0.0 {
+            return MaterialRecord::absorbed();
+        }
+
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        let f0 = self.albedo.value(u, v, rec.p.unwrap());
+        let (wi, attenuation) =
+            reflect(self.distribution, wo, |cosine| fresnel_schlick(cosine, f0));
+        MaterialRecord {
+            attenuation,
+            scattered: Some(Ray::with_time(rec.p.unwrap(), uvw.local(wi), ray.time())),
+            scatter: true,
+            pdf: Some(Box::new(MicrofacetPdf::new(
+                self.distribution,
+                rec.normal.unwrap(),
+                ray.direction(),
+                None,
+            ))),
+        }
+    }
+
+    fn bsdf_cos(
+        &self,
+        ray: &Ray,
+        rec: &HitRecord,
+        _mat_rec: &MaterialRecord,
+        scattered: &Ray,
+    ) -> Color {
+        let uvw = Onb::from_w(rec.normal.unwrap());
+        let wo = uvw.to_local(-ray.direction().unit_vector());
+        let wi = uvw.to_local(scattered.direction().unit_vector());
+        let (u, v) = rec.uv.unwrap_or((0.0, 0.0));
+        let f0 = self.albedo.value(u, v, rec.p.unwrap());
+        self.distribution
+            .reflection(wo, wi, |cosine| fresnel_schlick(cosine, f0))
+    }
+}
+
+#[test]
+fn rough_conductor_conserves_energy() {
+    use crate::{Point3, Vec3};
+
+    crate::seed_thread_rng(Some(7));
+    let mut rec = HitRecord::new();
+    rec.p = Some(Point3::new(0.0, 0.0, 0.0));
+    rec.normal = Some(Vec3::new(0.0, 1.0, 0.0));
+    rec.front_face = Some(true);
+    let ray = Ray::new(Point3::new(-1.0, 1.0, 0.0), Vec3::new(1.0, -1.0, 0.0));
+
+    // A white furnace: a perfect reflector never returns more than it gets,
+    // and its pdf reproduces the sampled weights.
+    let white = RoughConductor::new(Color::new(1.0, 1.0, 1.0), 0.5);
+    let samples = 20_000;
+    let mut total = 0.0;
+    for _ in 0..samples {
+        let srec = white.scatter(ray, rec.clone());
+        let weight = srec.attenuation.x();
+        assert!(weight <= 1.0 + 1e-9, "{}", weight);
+        total += weight;
+
+        let pdf = srec.pdf.as_ref().unwrap();
+        let direction = pdf.generate();
+        let scattered = Ray::new(rec.p.unwrap(), direction);
+        let pdf_value = pdf.value(direction);
+        if pdf_value > 0.0 {
+            let ratio = white.bsdf_cos(&ray, &rec, &srec, &scattered).x() / pdf_value;
+            assert!(ratio <= 1.0 + 1e-9, "{}", ratio);
+        }
+    }
+    let mean = total / samples as f64;
+    assert!(mean > 0.85 && mean <= 1.0, "{}", mean);
+
+    // Without roughness it is a mirror.
+    let mirror = RoughConductor::new(Color::new(1.0, 1.0, 1.0), 0.0);
+    let srec = mirror.scatter(ray, rec);
+    crate::seed_thread_rng(None);
+    let direction = srec.scattered.unwrap().direction();
+    assert!((direction - Vec3::new(1.0, 1.0, 0.0).unit_vector()).length() < 0.01);
+    assert!((srec.attenuation - Color::new(1.0, 1.0, 1.0)).length() < 0.01);
+}
@@ -0,0 +1,1433 @@
+use std::{
+    collections::{HashMap, HashSet},
+    error::Error,
+    fmt, fs, io,
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::{
+    load_obj, vec3::cross, AaRect, Aabb, Background, BvhNode, Camera, CheckerTexture, Color,
+    Conductor, ConstantMedium, Cuboid, DensityGrid, Dielectric, DiffuseLight, Dispersion,
+    HenyeyGreenstein, HeterogeneousMedium, Hittable, HittableList, ImageTexture, Instance,
+    Isotropic, Lambertian, MarbleTexture, Material, Matrix4, Metal, MovingSphere, NoiseTexture,
+    Perlin, Plane, Principled, Quad, RenderSettings, RoughConductor, RoughDielectric, SolidColor,
+    Sphere, SplitHeuristic, Texture, Triangle, Vec3, WoodTexture, CONDUCTOR_PRESETS, LAMBDA_MAX,
+    LAMBDA_MIN,
+};
+
+/// A camera, world and render settings built from a scene file.
+pub struct Scene {
+    pub camera: Camera,
+    pub world: Arc<dyn Hittable>,
+    /// Emitters to sample directly, or `None` if there are none.
+    pub lights: Option<Arc<dyn Hittable>>,
+    pub background: Background,
+    pub settings: RenderSettings,
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        file: String,
+        line: Option<usize>,
+        message: String,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            SceneError::Parse {
+                file,
+                line: Some(line),
+                message,
+            } => write!(f, "{}:{}: {}", file, line, message),
+            SceneError::Parse {
+                file,
+                line: None,
+                message,
+            } => write!(f, "{}: {}", file, message),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io { source, .. } => Some(source),
+            SceneError::Parse { .. } => None,
+        }
+    }
+}
+
+/// Loads a TOML scene file. Mesh paths are resolved relative to the file.
+pub fn load_scene(path: impl AsRef<Path>) -> Result<Scene, SceneError> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path).map_err(|source| SceneError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+    parse_scene(&source, &path.display().to_string(), base_dir)
+}
+
+/// Parses TOML scene source text.
+///
+/// # Examples
+/// ```
+/// use std::path::Path;
+/// let source = r#"
+///     [image]
+///     width = 40
+///     height = 20
+///
+///     [camera]
+///     look_from = [0, 0, 1]
+///     look_at = [0, 0, 0]
+///     vfov = 90
+///
+///     [materials.grey]
+///     type = "lambertian"
+///     albedo = [0.5, 0.5, 0.5]
+///
+///     [[objects]]
+///     type = "sphere"
+///     center = [0, 0, -1]
+///     radius = 0.5
+///     material = "grey"
+/// "#;
+/// let scene = ray_tracer::parse_scene(source, "example.toml", Path::new("")).unwrap();
+/// assert_eq!(scene.settings.image_height, 20);
+/// ```
+pub fn parse_scene(source: &str, file: &str, base_dir: &Path) -> Result<Scene, SceneError> {
+    let err = |span: Option<Range<usize>>, message: String| SceneError::Parse {
+        file: file.to_string(),
+        line: span.map(|span| line_of(source, span.start)),
+        message,
+    };
+
+    let desc: SceneDesc =
+        toml::from_str(source).map_err(|e| err(e.span(), e.message().to_string()))?;
+
+    // Image
+    let image_span = desc.image.span();
+    let image = desc.image.into_inner();
+    let image_height = match (image.height, image.aspect_ratio) {
+        (Some(height), None) => height,
+        (None, Some(aspect_ratio)) => (image.width as f64 / aspect_ratio) as usize,
+        (None, None) => {
+            return Err(err(
+                Some(image_span),
+                String::from("image needs a height or aspect_ratio"),
+            ))
+        }
+        (Some(_), Some(_)) => {
+            return Err(err(
+                Some(image_span),
+                String::from("image takes a height or an aspect_ratio, not both"),
+            ))
+        }
+    };
+    // Pixel coordinates are divided by the width and height less one.
+    if image.width < 2 || image_height < 2 {
+        return Err(err(
+            Some(image_span),
+            format!(
+                "image must be at least 2x2 pixels, got {}x{}",
+                image.width, image_height
+            ),
+        ));
+    }
+    let mut settings = RenderSettings::new(image.width, image_height);
+    settings.samples_per_pixel = image.samples_per_pixel;
+    settings.max_depth = image.max_depth;
+
+    // Camera
+    let camera_span = desc.camera.span();
+    let camera_desc = desc.camera.into_inner();
+    let [shutter_open, shutter_close] = camera_desc.shutter;
+    let look_from = to_vec3(camera_desc.look_from);
+    let look_at = to_vec3(camera_desc.look_at);
+    let vup = to_vec3(camera_desc.vup);
+    let focus_distance = camera_desc
+        .focus_distance
+        .unwrap_or_else(|| (look_from - look_at).length());
+    let camera_error = if look_from == look_at {
+        Some("camera look_from and look_at must differ")
+    } else if cross(&vup, &(look_from - look_at).unit_vector()).near_zero() {
+        Some("camera vup must not be zero or parallel to the view direction")
+    } else if !(camera_desc.vfov > 0.0 && camera_desc.vfov < 180.0) {
+        Some("camera vfov must be between 0 and 180 degrees")
+    } else if !(focus_distance > 0.0 && focus_distance.is_finite()) {
+        Some("camera focus_distance must be a positive number")
+    } else {
+        None
+    };
+    if let Some(message) = camera_error {
+        return Err(err(Some(camera_span), String::from(message)));
+    }
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        vup,
+        camera_desc.vfov,
+        settings.aspect_ratio(),
+        camera_desc.aperture,
+        focus_distance,
+    )
+    .with_shutter(shutter_open, shutter_close);
+
+    // Textures
+    let perlin = |seed: Option<u64>| seed.map_or_else(Perlin::new, Perlin::with_seed);
+    let mut textures: HashMap<String, Arc<dyn Texture>> = HashMap::new();
+    for (name, table) in desc.textures {
+        let span = table.span();
+        let texture: Arc<dyn Texture> = match parse_table(table, &err)? {
+            TextureDesc::Solid { color } => Arc::new(SolidColor::new(to_vec3(color))),
+            TextureDesc::Checker { scale, even, odd } => Arc::new(CheckerTexture::from_colors(
+                scale,
+                to_vec3(even),
+                to_vec3(odd),
+            )),
+            TextureDesc::Noise { scale, seed } => Arc::new(NoiseTexture::new(perlin(seed), scale)),
+            TextureDesc::Marble {
+                scale,
+                turbulence,
+                base,
+                vein,
+                seed,
+            } => Arc::new(MarbleTexture::new(
+                perlin(seed),
+                scale,
+                turbulence,
+                to_vec3(base),
+                to_vec3(vein),
+            )),
+            TextureDesc::Wood {
+                scale,
+                turbulence,
+                light,
+                dark,
+                seed,
+            } => Arc::new(WoodTexture::new(
+                perlin(seed),
+                scale,
+                turbulence,
+                to_vec3(light),
+                to_vec3(dark),
+            )),
+            TextureDesc::Image { path } => Arc::new(
+                ImageTexture::load(base_dir.join(path))
+                    .map_err(|e| err(Some(span), e.to_string()))?,
+            ),
+        };
+        textures.insert(name, texture);
+    }
+
+    // Materials
+    let mut materials: HashMap<String, Arc<dyn Material>> = HashMap::new();
+    let mut emitters: HashSet<String> = HashSet::new();
+    for (name, table) in desc.materials {
+        let span = table.span();
+        let texture = |desc: ColorOrTexture| -> Result<Arc<dyn Texture>, SceneError> {
+            match desc {
+                ColorOrTexture::Color(color) => Ok(Arc::new(SolidColor::new(to_vec3(color)))),
+                ColorOrTexture::Texture(name) => textures
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| err(Some(span.clone()), format!("unknown texture '{}'", name))),
+            }
+        };
+        let roughness = |roughness: f64| {
+            if (0.0..=1.0).contains(&roughness) {
+                Ok(roughness)
+            } else {
+                Err(err(
+                    Some(span.clone()),
+                    String::from("roughness must be between 0 and 1"),
+                ))
+            }
+        };
+        let index_of_refraction = |index: f64| {
+            if index > 0.0 && index.is_finite() {
+                Ok(index)
+            } else {
+                Err(err(
+                    Some(span.clone()),
+                    String::from("index_of_refraction must be a positive number"),
+                ))
+            }
+        };
+
+        let material: Arc<dyn Material> = match parse_table(table, &err)? {
+            MaterialDesc::Lambertian { albedo } => {
+                Arc::new(Lambertian::from_texture(texture(albedo)?))
+            }
+            MaterialDesc::Metal { albedo, fuzz } => {
+                Arc::new(Metal::from_texture(texture(albedo)?, fuzz))
+            }
+            MaterialDesc::Dielectric {
+                index_of_refraction,
+                dispersion,
+                absorption,
+                transmittance,
+            } => {
+                let mut dielectric = match (index_of_refraction, dispersion) {
+                    (index_of_refraction, Some(dispersion)) => {
+                        let dispersion = match dispersion {
+                            DispersionDesc::Bk7 => Dispersion::BK7,
+                            DispersionDesc::Cauchy([a, b]) => Dispersion::Cauchy { a, b },
+                            DispersionDesc::Sellmeier { b, c } => Dispersion::Sellmeier { b, c },
+                        };
+                        if !dispersion.is_valid() {
+                            return Err(err(
+                                Some(span),
+                                format!(
+                                    "dispersion must give a positive index of refraction from {} to {} nm",
+                                    LAMBDA_MIN, LAMBDA_MAX
+                                ),
+                            ));
+                        }
+                        let mut dielectric = Dielectric::with_dispersion(dispersion);
+                        if let Some(index_of_refraction) = index_of_refraction {
+                            dielectric.index_of_refraction = index_of_refraction;
+                        }
+                        dielectric
+                    }
+                    (Some(index_of_refraction), None) => Dielectric::new(index_of_refraction),
+                    (None, None) => {
+                        return Err(err(
+                            Some(span),
+                            String::from(
+                                "a dielectric needs an index_of_refraction or a dispersion",
+                            ),
+                        ))
+                    }
+                };
+                match (absorption, transmittance) {
+                    (Some(_), Some(_)) => {
+                        return Err(err(
+                            Some(span),
+                            String::from("give either absorption or transmittance, not both"),
+                        ))
+                    }
+                    (Some(absorption), None) => {
+                        dielectric = dielectric
+                            .with_absorption(to_vec3(absorption))
+                            .map_err(|message| err(Some(span.clone()), message))?
+                    }
+                    (None, Some(transmittance)) => {
+                        dielectric = dielectric
+                            .with_transmittance(to_vec3(transmittance), 1.0)
+                            .map_err(|message| err(Some(span.clone()), message))?
+                    }
+                    (None, None) => {}
+                }
+                Arc::new(dielectric)
+            }
+            MaterialDesc::RoughConductor {
+                albedo,
+                roughness: value,
+            } => Arc::new(RoughConductor::from_texture(
+                texture(albedo)?,
+                roughness(value)?,
+            )),
+            MaterialDesc::Conductor {
+                metal,
+                eta,
+                k,
+                roughness: value,
+            } => match (metal, eta, k, roughness(value)?) {
+                (Some(metal), None, None, roughness) => {
+                    Arc::new(Conductor::preset(&metal, roughness).ok_or_else(|| {
+                        err(
+                            Some(span.clone()),
+                            format!(
+                                "unknown metal '{}', expected one of: {}",
+                                metal,
+                                CONDUCTOR_PRESETS.join(", ")
+                            ),
+                        )
+                    })?)
+                }
+                (None, Some(eta), Some(k), roughness) => {
+                    let eta_valid = eta.iter().all(|&n| n > 0.0 && n.is_finite());
+                    let k_valid = k.iter().all(|&k| k >= 0.0 && k.is_finite());
+                    if !(eta_valid && k_valid) {
+                        return Err(err(
+                            Some(span),
+                            String::from("eta must be positive and k non-negative"),
+                        ));
+                    }
+                    Arc::new(Conductor::new(to_vec3(eta), to_vec3(k), roughness))
+                }
+                _ => {
+                    return Err(err(
+                        Some(span),
+                        String::from("a conductor needs either a metal or both eta and k"),
+                    ))
+                }
+            },
+            MaterialDesc::RoughDielectric {
+                index_of_refraction: index,
+                roughness: value,
+            } => Arc::new(RoughDielectric::new(
+                index_of_refraction(index)?,
+                roughness(value)?,
+            )),
+            MaterialDesc::Principled {
+                base_color,
+                metallic,
+                roughness,
+                specular,
+                clearcoat,
+                sheen,
+                transmission,
+                index_of_refraction: index,
+            } => {
+                let defaults = Principled::new(texture(base_color)?);
+                let scalar = |desc: Option<FloatOrTexture>, default: Arc<dyn Texture>| match desc {
+                    None => Ok(default),
+                    Some(FloatOrTexture::Float(value)) => {
+                        texture(ColorOrTexture::Color([value, value, value]))
+                    }
+                    Some(FloatOrTexture::Texture(name)) => texture(ColorOrTexture::Texture(name)),
+                };
+                Arc::new(Principled {
+                    metallic: scalar(metallic, defaults.metallic.clone())?,
+                    roughness: scalar(roughness, defaults.roughness.clone())?,
+                    specular: scalar(specular, defaults.specular.clone())?,
+                    clearcoat: scalar(clearcoat, defaults.clearcoat.clone())?,
+                    sheen: scalar(sheen, defaults.sheen.clone())?,
+                    transmission: scalar(transmission, defaults.transmission.clone())?,
+                    index_of_refraction: match index {
+                        Some(index) => index_of_refraction(index)?,
+                        None => defaults.index_of_refraction,
+                    },
+                    ..defaults
+                })
+            }
+            MaterialDesc::HenyeyGreenstein { albedo, g } => {
+                Arc::new(HenyeyGreenstein::from_texture(texture(albedo)?, g))
+            }
+            MaterialDesc::Isotropic { albedo } => {
+                Arc::new(Isotropic::from_texture(texture(albedo)?))
+            }
+            MaterialDesc::DiffuseLight { emit } => {
+                emitters.insert(name.clone());
+                Arc::new(DiffuseLight::from_texture(texture(emit)?))
+            }
+        };
+        materials.insert(name, material);
+    }
+
+    // Objects
+    let mut objects: Vec<Arc<dyn Hittable>> = Vec::new();
+    let mut lights: Vec<Arc<dyn Hittable>> = Vec::new();
+    // Meshes loaded for instancing, so repeated placements share one BVH.
+    let mut meshes: HashMap<(String, Option<String>), Arc<dyn Hittable>> = HashMap::new();
+    for mut table in desc.objects {
+        let span = table.span();
+        let transform = match table.get_mut().remove("transform") {
+            Some(value) => {
+                Some(parse_transform(value).map_err(|message| err(Some(span.clone()), message))?)
+            }
+            None => None,
+        };
+        let density = match table.get_mut().remove("density") {
+            Some(value) => match value.as_float().or(value.as_integer().map(|i| i as f64)) {
+                Some(density) if density > 0.0 => Some(density),
+                _ => {
+                    return Err(err(
+                        Some(span),
+                        String::from("density must be a positive number"),
+                    ))
+                }
+            },
+            None => None,
+        };
+        let material = |name: &str| {
+            materials
+                .get(name)
+                .cloned()
+                .ok_or_else(|| err(Some(span.clone()), format!("unknown material '{}'", name)))
+        };
+
+        let object: ObjectDesc = parse_table(table, &err)?;
+        // With a density, the object is the boundary of a medium whose
+        // phase function is the object's material.
+        let phase_function = match (density, object.material_name()) {
+            (None, _) => None,
+            (Some(_), Some(name)) => Some(material(name)?),
+            (Some(_), None) => {
+                return Err(err(Some(span), String::from("a medium needs a material")))
+            }
+        };
+
+        // Only shapes that can sample themselves are used as lights.
+        let is_light = density.is_none()
+            && matches!(
+                object,
+                ObjectDesc::Sphere { .. }
+                    | ObjectDesc::Quad { .. }
+                    | ObjectDesc::Rect { .. }
+                    | ObjectDesc::Cuboid { .. }
+            )
+            && object
+                .material_name()
+                .is_some_and(|name| emitters.contains(name));
+
+        let mut made: Vec<Arc<dyn Hittable>> = Vec::new();
+        match object {
+            ObjectDesc::Sphere {
+                center,
+                radius,
+                material: name,
+            } => made.push(Arc::new(Sphere::new(
+                to_vec3(center),
+                radius,
+                material(&name)?,
+            ))),
+            ObjectDesc::MovingSphere {
+                centers: [center0, center1],
+                radius,
+                material: name,
+            } => {
+                // The centers are keyframed at the shutter times, so with an
+                // instantaneous shutter the sphere would never move.
+                if shutter_close <= shutter_open {
+                    return Err(err(
+                        Some(span),
+                        String::from(
+                            "a moving_sphere needs a camera shutter that closes after it opens",
+                        ),
+                    ));
+                }
+                made.push(Arc::new(MovingSphere::new(
+                    to_vec3(center0),
+                    to_vec3(center1),
+                    shutter_open,
+                    shutter_close,
+                    radius,
+                    material(&name)?,
+                )))
+            }
+            ObjectDesc::Triangle {
+                vertices: [v0, v1, v2],
+                material: name,
+            } => made.push(Arc::new(Triangle::new(
+                to_vec3(v0),
+                to_vec3(v1),
+                to_vec3(v2),
+                material(&name)?,
+            ))),
+            ObjectDesc::Quad {
+                q,
+                u,
+                v,
+                material: name,
+            } => {
+                let (u, v) = (to_vec3(u), to_vec3(v));
+                if cross(&u, &v).length_squared() == 0.0 {
+                    return Err(err(
+                        Some(span),
+                        String::from("quad edges u and v must not be parallel or zero"),
+                    ));
+                }
+                made.push(Arc::new(Quad::new(to_vec3(q), u, v, material(&name)?)))
+            }
+            ObjectDesc::Rect {
+                plane,
+                a: [a0, a1],
+                b: [b0, b1],
+                k,
+                material: name,
+            } => {
+                let plane = match plane.as_str() {
+                    "xy" => Plane::Xy,
+                    "xz" => Plane::Xz,
+                    "yz" => Plane::Yz,
+                    _ => {
+                        return Err(err(
+                            Some(span),
+                            format!("unknown plane '{}', expected xy, xz or yz", plane),
+                        ))
+                    }
+                };
+                if !(a0 < a1 && b0 < b1) {
+                    return Err(err(
+                        Some(span),
+                        String::from("rect bounds a and b must each be increasing"),
+                    ));
+                }
+                made.push(Arc::new(AaRect::new(
+                    plane,
+                    a0,
+                    a1,
+                    b0,
+                    b1,
+                    k,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Cuboid {
+                min,
+                max,
+                material: name,
+            } => {
+                if min.iter().zip(max).any(|(min, max)| *min == max) {
+                    return Err(err(
+                        Some(span),
+                        String::from("box corners must differ along every axis"),
+                    ));
+                }
+                made.push(Arc::new(Cuboid::new(
+                    to_vec3(min),
+                    to_vec3(max),
+                    material(&name)?,
+                )))
+            }
+            ObjectDesc::Volume {
+                min,
+                max,
+                grid,
+                resolution,
+                noise_scale,
+                seed,
+                density_scale,
+                material: name,
+            } => {
+                if !(density_scale > 0.0 && density_scale.is_finite()) {
+                    return Err(err(
+                        Some(span),
+                        String::from("density_scale must be a positive number"),
+                    ));
+                }
+                if !(1..=MAX_VOLUME_RESOLUTION).contains(&resolution) {
+                    return Err(err(
+                        Some(span),
+                        format!("resolution must be between 1 and {}", MAX_VOLUME_RESOLUTION),
+                    ));
+                }
+                let bounds = Aabb::new(to_vec3(min), to_vec3(max));
+                let grid = match grid {
+                    Some(path) => DensityGrid::load(base_dir.join(path), bounds)
+                        .map_err(|e| err(Some(span.clone()), e.to_string()))?,
+                    None => {
+                        let noise = perlin(seed);
+                        DensityGrid::from_fn(resolution, resolution, resolution, bounds, |p| {
+                            noise.turb(p * noise_scale, 7)
+                        })
+                    }
+                };
+                made.push(Arc::new(HeterogeneousMedium::new(
+                    Arc::new(grid),
+                    density_scale,
+                    material(&name)?,
+                )));
+            }
+            ObjectDesc::Mesh {
+                path,
+                material: name,
+            } => {
+                let key = (path, name);
+                if let Some(mesh) = meshes.get(&key) {
+                    made.push(mesh.clone());
+                } else {
+                    let default_material = match &key.1 {
+                        Some(name) => material(name)?,
+                        None => Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5))),
+                    };
+                    let model = load_obj(base_dir.join(&key.0), default_material)
+                        .map_err(|e| err(Some(span.clone()), e.to_string()))?;
+                    if transform.is_some() {
+                        let mesh: Arc<dyn Hittable> =
+                            Arc::new(BvhNode::new(model.triangles(), SplitHeuristic::Sah));
+                        meshes.insert(key, mesh.clone());
+                        made.push(mesh);
+                    } else {
+                        made.extend(model.triangles());
+                    }
+                }
+            }
+        }
+
+        if let (Some(density), Some(phase_function)) = (density, phase_function) {
+            let boundary: Arc<dyn Hittable> = if made.len() == 1 {
+                made.pop().unwrap()
+            } else {
+                Arc::new(BvhNode::new(made, SplitHeuristic::Sah))
+            };
+            made = vec![Arc::new(ConstantMedium::with_phase_function(
+                boundary,
+                density,
+                phase_function,
+            ))];
+        }
+
+        match transform {
+            Some(transform) => {
+                let object: Arc<dyn Hittable> = if made.len() == 1 {
+                    made.pop().unwrap()
+                } else {
+                    Arc::new(BvhNode::new(made, SplitHeuristic::Sah))
+                };
+                objects.push(Arc::new(Instance::new(object, transform)));
+            }
+            None => objects.extend(made),
+        }
+        if is_light {
+            lights.push(objects.last().unwrap().clone());
+        }
+    }
+
+    if objects.is_empty() {
+        return Err(err(None, String::from("scene contains no objects")));
+    }
+
+    let world: Arc<dyn Hittable> = if desc.bvh {
+        Arc::new(BvhNode::new(objects, SplitHeuristic::Sah))
+    } else {
+        Arc::new(HittableList { objects })
+    };
+
+    let background = match desc.background {
+        Some(color) => Background::Solid(to_vec3(color)),
+        None => Background::Sky,
+    };
+
+    let lights: Option<Arc<dyn Hittable>> = if lights.is_empty() {
+        None
+    } else {
+        Some(Arc::new(HittableList { objects: lights }))
+    };
+
+    Ok(Scene {
+        camera,
+        world,
+        lights,
+        background,
+        settings,
+    })
+}
+
+/// Builds the matrix for an object's list of transforms, applied in order.
+fn parse_transform(value: toml::Value) -> Result<Matrix4, String> {
+    let steps: Vec<TransformDesc> = value
+        .try_into()
+        .map_err(|e: toml::de::Error| format!("invalid transform: {}", e.message()))?;
+
+    let mut matrix = Matrix4::identity();
+    for step in steps {
+        let step = match step {
+            TransformDesc::Translate(offset) => Matrix4::translation(to_vec3(offset)),
+            TransformDesc::Scale(ScaleDesc::Uniform(factor)) => {
+                Matrix4::scaling(Vec3::new(factor, factor, factor))
+            }
+            TransformDesc::Scale(ScaleDesc::PerAxis(factors)) => Matrix4::scaling(to_vec3(factors)),
+            TransformDesc::Rotate { axis, angle } => Matrix4::rotation(to_vec3(axis), angle),
+            TransformDesc::RotateX(angle) => Matrix4::rotation(Vec3::new(1.0, 0.0, 0.0), angle),
+            TransformDesc::RotateY(angle) => Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), angle),
+            TransformDesc::RotateZ(angle) => Matrix4::rotation(Vec3::new(0.0, 0.0, 1.0), angle),
+        };
+        matrix = step * matrix;
+    }
+
+    match matrix.inverse() {
+        Some(_) => Ok(matrix),
+        None => Err(String::from("transform is not invertible")),
+    }
+}
+
+/// Deserializes one table, reporting errors at the table's position since
+/// tagged enums lose the position of individual fields.
+fn parse_table<T, E>(table: Spanned<toml::Table>, err: &E) -> Result<T, SceneError>
+where
+    T: for<'de> Deserialize<'de>,
+    E: Fn(Option<Range<usize>>, String) -> SceneError,
+{
+    let span = table.span();
+    toml::Value::Table(table.into_inner())
+        .try_into()
+        .map_err(|e: toml::de::Error| err(Some(span), e.message().to_string()))
+}
+
+/// Returns the 1-based line containing the byte `offset`.
+fn line_of(source: &str, offset: usize) -> usize {
+    source[..offset.min(source.len())].matches('\n').count() + 1
+}
+
+fn to_vec3(v: [f64; 3]) -> Vec3 {
+    Vec3::new(v[0], v[1], v[2])
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    image: Spanned<ImageDesc>,
+    camera: Spanned<CameraDesc>,
+    #[serde(default)]
+    textures: HashMap<String, Spanned<toml::Table>>,
+    #[serde(default)]
+    materials: HashMap<String, Spanned<toml::Table>>,
+    #[serde(default)]
+    objects: Vec<Spanned<toml::Table>>,
+    #[serde(default = "default_bvh")]
+    bvh: bool,
+    /// Constant background color; the sky gradient if absent.
+    background: Option<[f64; 3]>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ImageDesc {
+    width: usize,
+    height: Option<usize>,
+    aspect_ratio: Option<f64>,
+    #[serde(default = "default_samples_per_pixel")]
+    samples_per_pixel: usize,
+    #[serde(default = "default_max_depth")]
+    max_depth: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    look_from: [f64; 3],
+    look_at: [f64; 3],
+    #[serde(default = "default_vup")]
+    vup: [f64; 3],
+    vfov: f64,
+    #[serde(default)]
+    aperture: f64,
+    focus_distance: Option<f64>,
+    /// Shutter open and close times, for motion blur.
+    #[serde(default)]
+    shutter: [f64; 2],
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum MaterialDesc {
+    Lambertian {
+        albedo: ColorOrTexture,
+    },
+    Metal {
+        albedo: ColorOrTexture,
+        #[serde(default)]
+        fuzz: f64,
+    },
+    /// Needs an `index_of_refraction`, a `dispersion`, or both, where the
+    /// index is used for RGB rendering.
+    ///
+    /// Glass is tinted by an `absorption` coefficient, or equivalently by
+    /// the `transmittance` left after one unit of distance inside.
+    Dielectric {
+        index_of_refraction: Option<f64>,
+        dispersion: Option<DispersionDesc>,
+        absorption: Option<[f64; 3]>,
+        transmittance: Option<[f64; 3]>,
+    },
+    RoughConductor {
+        albedo: ColorOrTexture,
+        roughness: f64,
+    },
+    /// A named `metal` preset, or a complex index of refraction.
+    Conductor {
+        metal: Option<String>,
+        eta: Option<[f64; 3]>,
+        k: Option<[f64; 3]>,
+        #[serde(default)]
+        roughness: f64,
+    },
+    RoughDielectric {
+        index_of_refraction: f64,
+        roughness: f64,
+    },
+    Principled {
+        base_color: ColorOrTexture,
+        metallic: Option<FloatOrTexture>,
+        roughness: Option<FloatOrTexture>,
+        specular: Option<FloatOrTexture>,
+        clearcoat: Option<FloatOrTexture>,
+        sheen: Option<FloatOrTexture>,
+        transmission: Option<FloatOrTexture>,
+        index_of_refraction: Option<f64>,
+    },
+    DiffuseLight {
+        emit: ColorOrTexture,
+    },
+    Isotropic {
+        albedo: ColorOrTexture,
+    },
+    HenyeyGreenstein {
+        albedo: ColorOrTexture,
+        /// Anisotropy, from -1 (backward) to 1 (forward).
+        #[serde(default)]
+        g: f64,
+    },
+}
+
+/// A named glass, or coefficients for a dispersion formula.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum DispersionDesc {
+    Bk7,
+    Cauchy([f64; 2]),
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+/// A color given inline or the name of a texture.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorOrTexture {
+    Color([f64; 3]),
+    Texture(String),
+}
+
+/// A number given inline or the name of a texture, read from its red
+/// channel.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum FloatOrTexture {
+    Float(f64),
+    Texture(String),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum TextureDesc {
+    Solid {
+        color: [f64; 3],
+    },
+    Checker {
+        #[serde(default = "default_checker_scale")]
+        scale: f64,
+        even: [f64; 3],
+        odd: [f64; 3],
+    },
+    Noise {
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        seed: Option<u64>,
+    },
+    Marble {
+        #[serde(default = "default_noise_scale")]
+        scale: f64,
+        #[serde(default = "default_marble_turbulence")]
+        turbulence: f64,
+        #[serde(default = "default_marble_base")]
+        base: [f64; 3],
+        #[serde(default)]
+        vein: [f64; 3],
+        seed: Option<u64>,
+    },
+    Wood {
+        #[serde(default = "default_wood_scale")]
+        scale: f64,
+        #[serde(default = "default_noise_scale")]
+        turbulence: f64,
+        #[serde(default = "default_wood_light")]
+        light: [f64; 3],
+        #[serde(default = "default_wood_dark")]
+        dark: [f64; 3],
+        seed: Option<u64>,
+    },
+    Image {
+        path: String,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+enum ObjectDesc {
+    Sphere {
+        center: [f64; 3],
+        radius: f64,
+        material: String,
+    },
+    MovingSphere {
+        /// Centers at the shutter's open and close times.
+        centers: [[f64; 3]; 2],
+        radius: f64,
+        material: String,
+    },
+    Triangle {
+        vertices: [[f64; 3]; 3],
+        material: String,
+    },
+    Quad {
+        /// A corner and the two edges leaving it.
+        q: [f64; 3],
+        u: [f64; 3],
+        v: [f64; 3],
+        material: String,
+    },
+    Rect {
+        /// One of "xy", "xz" or "yz".
+        plane: String,
+        /// Extents along the first and second in-plane axes.
+        a: [f64; 2],
+        b: [f64; 2],
+        /// Position along the normal axis.
+        k: f64,
+        material: String,
+    },
+    #[serde(rename = "box")]
+    Cuboid {
+        min: [f64; 3],
+        max: [f64; 3],
+        material: String,
+    },
+    /// A heterogeneous medium filling the box between `min` and `max`,
+    /// with densities from a grid file or, without one, Perlin turbulence.
+    Volume {
+        min: [f64; 3],
+        max: [f64; 3],
+        grid: Option<String>,
+        #[serde(default = "default_volume_resolution")]
+        resolution: usize,
+        #[serde(default = "default_noise_scale")]
+        noise_scale: f64,
+        seed: Option<u64>,
+        #[serde(default = "default_density_scale")]
+        density_scale: f64,
+        material: String,
+    },
+    Mesh {
+        path: String,
+        material: Option<String>,
+    },
+}
+
+impl ObjectDesc {
+    fn material_name(&self) -> Option<&str> {
+        match self {
+            ObjectDesc::Sphere { material, .. }
+            | ObjectDesc::MovingSphere { material, .. }
+            | ObjectDesc::Triangle { material, .. }
+            | ObjectDesc::Quad { material, .. }
+            | ObjectDesc::Rect { material, .. }
+            | ObjectDesc::Cuboid { material, .. }
+            | ObjectDesc::Volume { material, .. } => Some(material),
+            ObjectDesc::Mesh { material, .. } => material.as_deref(),
+        }
+    }
+}
+
+/// One step of an object's `transform` list, with angles in degrees.
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformDesc {
+    Translate([f64; 3]),
+    Scale(ScaleDesc),
+    Rotate { axis: [f64; 3], angle: f64 },
+    RotateX(f64),
+    RotateY(f64),
+    RotateZ(f64),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScaleDesc {
+    Uniform(f64),
+    PerAxis([f64; 3]),
+}
+
+fn default_bvh() -> bool {
+    true
+}
+
+fn default_checker_scale() -> f64 {
+    1.0
+}
+
+/// Largest procedural volume grid, per side: 512³ voxels take 512 MiB.
+const MAX_VOLUME_RESOLUTION: usize = 512;
+
+fn default_volume_resolution() -> usize {
+    64
+}
+
+fn default_density_scale() -> f64 {
+    1.0
+}
+
+fn default_noise_scale() -> f64 {
+    1.0
+}
+
+fn default_marble_turbulence() -> f64 {
+    10.0
+}
+
+fn default_marble_base() -> [f64; 3] {
+    [1.0, 1.0, 1.0]
+}
+
+fn default_wood_scale() -> f64 {
+    8.0
+}
+
+fn default_wood_light() -> [f64; 3] {
+    [0.75, 0.55, 0.35]
+}
+
+fn default_wood_dark() -> [f64; 3] {
+    [0.45, 0.27, 0.12]
+}
+
+fn default_samples_per_pixel() -> usize {
+    100
+}
+
+fn default_max_depth() -> i32 {
+    50
+}
+
+fn default_vup() -> [f64; 3] {
+    [0.0, 1.0, 0.0]
+}
+
+#[cfg(test)]
+const TEST_SCENE: &str = r#"
+[image]
+width = 300
+aspect_ratio = 1.5
+
+[camera]
+look_from = [13, 2, 3]
+look_at = [0, 0, 0]
+vfov = 20
+aperture = 0.1
+focus_distance = 10
+
+[textures.checker]
+type = "checker"
+scale = 0.5
+even = [0.2, 0.3, 0.1]
+odd = [0.9, 0.9, 0.9]
+
+[materials.ground]
+type = "lambertian"
+albedo = "checker"
+
+[[objects]]
+type = "sphere"
+center = [0, -1000, 0]
+radius = 1000
+material = "ground"
+
+[[objects]]
+type = "sphere"
+center = [0, 1, 0]
+radius = 1
+material = "glass"
+"#;
+
+/// The message of the error loading `source` as "test.toml".
+#[cfg(test)]
+fn load_error(source: &str) -> String {
+    parse_scene(source, "test.toml", Path::new(""))
+        .err()
+        .unwrap()
+        .to_string()
+}
+
+/// The test scene with its "glass" material defined by the TOML `desc`.
+#[cfg(test)]
+fn with_glass(desc: &str) -> String {
+    TEST_SCENE.replacen(
+        "[[objects]]",
+        &format!("[materials.glass]\n{}\n\n[[objects]]", desc),
+        1,
+    )
+}
+
+/// The test scene with its glass sphere replaced by the TOML `desc`, made
+/// of the ground material.
+#[cfg(test)]
+fn with_object(desc: &str) -> String {
+    TEST_SCENE
+        .replace("\"glass\"", "\"ground\"")
+        .replace("type = \"sphere\"\ncenter = [0, 1, 0]\nradius = 1\n", desc)
+}
+
+#[test]
+fn scene_errors_report_line_numbers() {
+    let err = parse_scene(TEST_SCENE, "test.toml", Path::new(""))
+        .err()
+        .unwrap();
+    match err {
+        SceneError::Parse { line, message, .. } => {
+            assert_eq!(message, "unknown material 'glass'");
+            assert_eq!(line, Some(29));
+        }
+        _ => panic!("expected a parse error"),
+    }
+
+    let source = TEST_SCENE.replace("radius = 1\n", "radius = \"big\"\n");
+    let err = load_error(&source);
+    assert!(err.starts_with("test.toml:29:"), "{}", err);
+    assert!(err.ends_with("invalid type: string \"big\", expected f64"));
+}
+
+#[test]
+fn can_parse_scene() {
+    let source = TEST_SCENE.replace("\"glass\"", "\"ground\"");
+    let scene = parse_scene(&source, "test.toml", Path::new("")).unwrap();
+    assert_eq!(scene.settings.image_width, 300);
+    assert_eq!(scene.settings.image_height, 200);
+    assert_eq!(scene.settings.samples_per_pixel, 100);
+    let ray = crate::Ray::new(crate::Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    let mut rec = crate::HitRecord::new();
+    assert!(scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(3.0));
+
+    let source = TEST_SCENE.replace("albedo = \"checker\"", "albedo = \"marble\"");
+    assert_eq!(
+        load_error(&source),
+        "test.toml:19: unknown texture 'marble'"
+    );
+}
+
+#[test]
+fn rejects_invalid_tints() {
+    let glass = |tint: &str| {
+        with_glass(&format!(
+            "type = \"dielectric\"\nindex_of_refraction = 1.5\n{}",
+            tint
+        ))
+    };
+    assert_eq!(
+        load_error(&glass("absorption = [0, -1, 0]")),
+        "test.toml:23: absorption must be finite and non-negative"
+    );
+    for transmittance in ["[0, 0.5, 0.5]", "[0.5, 1.5, 0.5]"] {
+        let tint = format!("transmittance = {}", transmittance);
+        assert_eq!(
+            load_error(&glass(&tint)),
+            "test.toml:23: transmittance must be in (0, 1]"
+        );
+    }
+    let tint = "transmittance = [1, 0.5, 0.1]";
+    assert!(parse_scene(&glass(tint), "test.toml", Path::new("")).is_ok());
+}
+
+#[test]
+fn rejects_dispersions_without_a_real_index() {
+    // A Sellmeier pole at 500 nm, and a Cauchy index below zero.
+    for dispersion in [
+        "{ sellmeier = { b = [1, 0, 0], c = [0.25, 0, 0] } }",
+        "{ cauchy = [-1, 0.01] }",
+    ] {
+        let material = format!("type = \"dielectric\"\ndispersion = {}", dispersion);
+        assert_eq!(
+            load_error(&with_glass(&material)),
+            "test.toml:23: dispersion must give a positive index of refraction from 380 to 780 nm"
+        );
+    }
+    let material = "type = \"dielectric\"\ndispersion = \"bk7\"";
+    assert!(parse_scene(&with_glass(material), "test.toml", Path::new("")).is_ok());
+}
+
+#[test]
+fn can_transform_objects() {
+    let source = TEST_SCENE.replace("\"glass\"", "\"ground\"").replace(
+        "center = [0, 1, 0]\n",
+        "center = [0, 0, 0]\ntransform = [{ scale = 2 }, { rotate_y = 90 }, { translate = [0, 1, 0] }]\n",
+    );
+    let scene = parse_scene(&source, "test.toml", Path::new("")).unwrap();
+    let ray = crate::Ray::new(crate::Point3::new(0.0, 5.0, 0.0), Vec3::new(0.0, -1.0, 0.0));
+    let mut rec = crate::HitRecord::new();
+    assert!(scene.world.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    assert!((rec.t().unwrap() - 2.0).abs() < 1e-9);
+
+    let source = source.replace("{ scale = 2 }", "{ scale = [1, 0, 1] }");
+    assert_eq!(
+        load_error(&source),
+        "test.toml:29: transform is not invertible"
+    );
+}
+
+#[test]
+fn moving_spheres_need_an_open_shutter() {
+    let source =
+        with_object("type = \"moving_sphere\"\ncenters = [[0, 1, 0], [0, 2, 0]]\nradius = 1\n");
+    assert_eq!(
+        load_error(&source),
+        "test.toml:29: a moving_sphere needs a camera shutter that closes after it opens"
+    );
+
+    let source = source.replace(
+        "focus_distance = 10\n",
+        "focus_distance = 10\nshutter = [0, 1]\n",
+    );
+    assert!(parse_scene(&source, "test.toml", Path::new("")).is_ok());
+}
+
+#[test]
+fn rejects_flat_quads_and_boxes() {
+    assert_eq!(
+        load_error(&with_object(
+            "type = \"quad\"\nq = [0, 0, 0]\nu = [1, 0, 0]\nv = [2, 0, 0]\n"
+        )),
+        "test.toml:29: quad edges u and v must not be parallel or zero"
+    );
+    assert_eq!(
+        load_error(&with_object(
+            "type = \"box\"\nmin = [0, 0, 0]\nmax = [1, 0, 1]\n"
+        )),
+        "test.toml:29: box corners must differ along every axis"
+    );
+    for (a, b) in [("[1, 0]", "[0, 1]"), ("[0, 1]", "[2, 2]")] {
+        let rect = format!(
+            "type = \"rect\"\nplane = \"xz\"\na = {}\nb = {}\nk = 0\n",
+            a, b
+        );
+        assert_eq!(
+            load_error(&with_object(&rect)),
+            "test.toml:29: rect bounds a and b must each be increasing"
+        );
+    }
+}
+
+#[test]
+fn rejects_non_positive_densities() {
+    for density in ["0", "-1"] {
+        let source = TEST_SCENE.replace(
+            "radius = 1\n",
+            &format!("radius = 1\ndensity = {}\n", density),
+        );
+        assert_eq!(
+            load_error(&source),
+            "test.toml:29: density must be a positive number"
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_conductors() {
+    let material = "type = \"conductor\"\nmetal = \"unobtainium\"";
+    assert_eq!(
+        load_error(&with_glass(material)),
+        format!(
+            "test.toml:23: unknown metal 'unobtainium', expected one of: {}",
+            CONDUCTOR_PRESETS.join(", ")
+        )
+    );
+
+    for (eta, k) in [
+        ("[0.2, -1, 1]", "[3, 2, 2]"),
+        ("[0.2, 1, 1]", "[3, 2, nan]"),
+    ] {
+        let material = format!("type = \"conductor\"\neta = {}\nk = {}", eta, k);
+        assert_eq!(
+            load_error(&with_glass(&material)),
+            "test.toml:23: eta must be positive and k non-negative"
+        );
+    }
+    let material = "type = \"conductor\"\neta = [0.2, 1, 1]\nk = [3, 2, 0]";
+    assert!(parse_scene(&with_glass(material), "test.toml", Path::new("")).is_ok());
+}
+
+#[test]
+fn rejects_invalid_microfacet_parameters() {
+    for material in [
+        "type = \"rough_conductor\"\nalbedo = [1, 1, 1]\nroughness = 7",
+        "type = \"conductor\"\nmetal = \"gold\"\nroughness = -1",
+        "type = \"rough_dielectric\"\nindex_of_refraction = 1.5\nroughness = 1.5",
+    ] {
+        assert_eq!(
+            load_error(&with_glass(material)),
+            "test.toml:23: roughness must be between 0 and 1"
+        );
+    }
+    for index in ["0", "-1.5", "inf"] {
+        let material = format!(
+            "type = \"rough_dielectric\"\nindex_of_refraction = {}\nroughness = 0.5",
+            index
+        );
+        assert_eq!(
+            load_error(&with_glass(&material)),
+            "test.toml:23: index_of_refraction must be a positive number"
+        );
+    }
+    let material = "type = \"principled\"\nbase_color = [1, 1, 1]\nindex_of_refraction = 0";
+    assert_eq!(
+        load_error(&with_glass(material)),
+        "test.toml:23: index_of_refraction must be a positive number"
+    );
+    let material = "type = \"rough_dielectric\"\nindex_of_refraction = 1.5\nroughness = 1";
+    assert!(parse_scene(&with_glass(material), "test.toml", Path::new("")).is_ok());
+}
+
+#[test]
+fn rejects_invisible_volumes() {
+    let volume = "type = \"volume\"\nmin = [-1, 0, -1]\nmax = [1, 2, 1]\n";
+    let source = with_object(&format!("{}density_scale = 0\n", volume));
+    assert_eq!(
+        load_error(&source),
+        "test.toml:29: density_scale must be a positive number"
+    );
+
+    for resolution in [0, 1u64 << 40] {
+        let source = with_object(&format!("{}resolution = {}\n", volume, resolution));
+        assert_eq!(
+            load_error(&source),
+            "test.toml:29: resolution must be between 1 and 512"
+        );
+    }
+}
+
+#[test]
+fn rejects_degenerate_images() {
+    let source = TEST_SCENE.replace("width = 300", "width = 1");
+    assert_eq!(
+        load_error(&source),
+        "test.toml:2: image must be at least 2x2 pixels, got 1x0"
+    );
+
+    let source = TEST_SCENE.replace("aspect_ratio = 1.5", "height = 1");
+    assert_eq!(
+        load_error(&source),
+        "test.toml:2: image must be at least 2x2 pixels, got 300x1"
+    );
+}
+
+#[test]
+fn rejects_degenerate_cameras() {
+    let camera = |from: &str, to: &str| {
+        TEST_SCENE
+            .replace("\"glass\"", "\"ground\"")
+            .replace(from, to)
+    };
+    assert_eq!(
+        load_error(&camera("look_at = [0, 0, 0]", "look_at = [13, 2, 3]")),
+        "test.toml:6: camera look_from and look_at must differ"
+    );
+    assert_eq!(
+        load_error(&camera("vfov = 20", "vfov = 20\nvup = [13, 2, 3]")),
+        "test.toml:6: camera vup must not be zero or parallel to the view direction"
+    );
+    for vfov in ["vfov = 0", "vfov = 180"] {
+        assert_eq!(
+            load_error(&camera("vfov = 20", vfov)),
+            "test.toml:6: camera vfov must be between 0 and 180 degrees"
+        );
+    }
+    assert_eq!(
+        load_error(&camera("focus_distance = 10", "focus_distance = -1")),
+        "test.toml:6: camera focus_distance must be a positive number"
+    );
+}
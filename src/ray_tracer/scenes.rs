@@ -0,0 +1,339 @@
+use std::sync::Arc;
+
+use crate::{
+    random, random_in_range, vec3_random, vec3_random_in_range, AaRect, Background, BvhNode,
+    Camera, Color, ConstantMedium, Cuboid, Dielectric, DiffuseLight, Hittable, HittableList,
+    Instance, Lambertian, MarbleTexture, Material, Matrix4, Metal, Perlin, Plane, Point3,
+    RenderSettings, Scene, Sphere, SplitHeuristic, Vec3, WoodTexture,
+};
+
+/// Names accepted by `builtin_scene`.
+pub const BUILTIN_SCENES: &[&str] = &[
+    "random",
+    "simple_light",
+    "perlin_spheres",
+    "cornell_box",
+    "cornell_smoke",
+];
+
+/// Returns one of the scenes compiled into the crate.
+pub fn builtin_scene(name: &str) -> Option<Scene> {
+    match name {
+        "random" => Some(random_scene()),
+        "simple_light" => Some(simple_light()),
+        "perlin_spheres" => Some(perlin_spheres()),
+        "cornell_box" => Some(cornell_box()),
+        "cornell_smoke" => Some(cornell_smoke()),
+        _ => None,
+    }
+}
+
+/// Hundreds of small random spheres around three large ones.
+pub fn random_scene() -> Scene {
+    let mut settings = RenderSettings::new(1200, 800);
+    settings.samples_per_pixel = 100; // TODO: Is this the optimal sample size?
+    settings.max_depth = 50;
+
+    // World
+    let ground_material = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+
+    let mut world = HittableList {
+        objects: vec![Arc::new(Sphere::new(
+            Vec3::new(0.0, -1000.0, 0.0),
+            1000.0,
+            ground_material,
+        ))],
+    };
+
+    for a in -11..11 {
+        for b in -11..11 {
+            let choose_mat = random();
+            let center = Point3::new(a as f64 + 0.9 * random(), 0.2, b as f64 + 0.9 * random());
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).length() > 0.9 {
+                let sphere_material: Arc<dyn Material> = if choose_mat < 0.8 {
+                    // diffuse
+                    let albedo = vec3_random() * vec3_random();
+                    Arc::new(Lambertian::new(albedo))
+                } else if choose_mat < 0.95 {
+                    // metal
+                    let albedo = vec3_random_in_range(0.5, 1.0);
+                    let fuzz = random_in_range(0.0, 0.5);
+                    Arc::new(Metal::new(albedo, fuzz))
+                } else {
+                    // glass
+                    Arc::new(Dielectric::new(1.5))
+                };
+                world
+                    .objects
+                    .push(Arc::new(Sphere::new(center, 0.2, sphere_material)));
+            }
+        }
+    }
+
+    let material1 = Arc::new(Dielectric::new(1.5));
+    world.objects.push(Arc::new(Sphere::new(
+        Point3::new(0.0, 1.0, 0.0),
+        1.0,
+        material1,
+    )));
+
+    let material2 = Arc::new(Lambertian::new(Color::new(0.4, 0.2, 0.1)));
+    world.objects.push(Arc::new(Sphere::new(
+        Point3::new(-4.0, 1.0, 0.0),
+        1.0,
+        material2,
+    )));
+
+    let material3 = Arc::new(Metal::new(Color::new(0.7, 0.6, 0.5), 0.0));
+    world.objects.push(Arc::new(Sphere::new(
+        Point3::new(4.0, 1.0, 0.0),
+        1.0,
+        material3,
+    )));
+
+    // Camera
+    let look_from = Point3::new(13.0, 2.0, 3.0);
+    let look_at = Point3::new(0.0, 0.0, 0.0);
+    let camera = Camera::new(
+        look_from,
+        look_at,
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        settings.aspect_ratio(),
+        0.1,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world: Arc::new(BvhNode::from_list(&world, SplitHeuristic::Sah)),
+        lights: None,
+        background: Background::Sky,
+        settings,
+    }
+}
+
+/// A diffuse sphere lit only by a glowing sphere above it.
+pub fn simple_light() -> Scene {
+    let mut settings = RenderSettings::new(400, 225);
+    settings.samples_per_pixel = 400;
+
+    let ground = Arc::new(Lambertian::new(Color::new(0.5, 0.5, 0.5)));
+    let orange = Arc::new(Lambertian::new(Color::new(0.8, 0.4, 0.1)));
+    let light: Arc<dyn Hittable> = Arc::new(Sphere::new(
+        Point3::new(0.0, 7.0, 0.0),
+        2.0,
+        Arc::new(DiffuseLight::new(Color::new(4.0, 4.0, 4.0))),
+    ));
+
+    let world: HittableList<dyn Hittable> = HittableList {
+        objects: vec![
+            Arc::new(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, ground)),
+            Arc::new(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, orange)),
+            light.clone(),
+        ],
+    };
+
+    let camera = Camera::new(
+        Point3::new(26.0, 3.0, 6.0),
+        Point3::new(0.0, 2.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        settings.aspect_ratio(),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world: Arc::new(world),
+        lights: Some(light),
+        background: Background::Solid(Color::new(0.0, 0.0, 0.0)),
+        settings,
+    }
+}
+
+/// A marble ground plane and a wooden sphere, both from Perlin noise.
+pub fn perlin_spheres() -> Scene {
+    let settings = RenderSettings::new(400, 225);
+
+    let marble = Arc::new(Lambertian::from_texture(Arc::new(MarbleTexture::new(
+        Perlin::with_seed(1),
+        4.0,
+        10.0,
+        Color::new(0.9, 0.9, 0.9),
+        Color::new(0.2, 0.2, 0.25),
+    ))));
+    let wood = Arc::new(Lambertian::from_texture(Arc::new(WoodTexture::new(
+        Perlin::with_seed(2),
+        8.0,
+        1.0,
+        Color::new(0.75, 0.55, 0.35),
+        Color::new(0.45, 0.27, 0.12),
+    ))));
+
+    let world: HittableList<dyn Hittable> = HittableList {
+        objects: vec![
+            Arc::new(Sphere::new(Point3::new(0.0, -1000.0, 0.0), 1000.0, marble)),
+            Arc::new(Sphere::new(Point3::new(0.0, 2.0, 0.0), 2.0, wood)),
+        ],
+    };
+
+    let camera = Camera::new(
+        Point3::new(13.0, 2.0, 3.0),
+        Point3::new(0.0, 1.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        settings.aspect_ratio(),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world: Arc::new(world),
+        lights: None,
+        background: Background::Sky,
+        settings,
+    }
+}
+
+/// The Cornell box: a white room with a red and a green wall, lit by a
+/// square panel just below the ceiling, holding two turned white blocks.
+pub fn cornell_box() -> Scene {
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let light: Arc<dyn Hittable> = Arc::new(AaRect::new(
+        Plane::Xz,
+        213.0,
+        343.0,
+        227.0,
+        332.0,
+        554.0,
+        Arc::new(DiffuseLight::new(Color::new(15.0, 15.0, 15.0))),
+    ));
+
+    let mut objects = cornell_walls(white.clone());
+    objects.push(light.clone());
+    objects.push(rotated_block(
+        Vec3::new(165.0, 330.0, 165.0),
+        15.0,
+        Vec3::new(265.0, 0.0, 295.0),
+        white.clone(),
+    ));
+    objects.push(rotated_block(
+        Vec3::new(165.0, 165.0, 165.0),
+        -18.0,
+        Vec3::new(130.0, 0.0, 65.0),
+        white,
+    ));
+
+    cornell_scene(objects, light)
+}
+
+/// The Cornell box with its blocks replaced by black smoke and white fog,
+/// under a larger, dimmer light.
+pub fn cornell_smoke() -> Scene {
+    let white: Arc<dyn Material> = Arc::new(Lambertian::new(Color::new(0.73, 0.73, 0.73)));
+    let light: Arc<dyn Hittable> = Arc::new(AaRect::new(
+        Plane::Xz,
+        113.0,
+        443.0,
+        127.0,
+        432.0,
+        554.0,
+        Arc::new(DiffuseLight::new(Color::new(7.0, 7.0, 7.0))),
+    ));
+
+    let mut objects = cornell_walls(white.clone());
+    objects.push(light.clone());
+    objects.push(Arc::new(ConstantMedium::new(
+        rotated_block(
+            Vec3::new(165.0, 330.0, 165.0),
+            15.0,
+            Vec3::new(265.0, 0.0, 295.0),
+            white.clone(),
+        ),
+        0.01,
+        Color::new(0.0, 0.0, 0.0),
+    )));
+    objects.push(Arc::new(ConstantMedium::new(
+        rotated_block(
+            Vec3::new(165.0, 165.0, 165.0),
+            -18.0,
+            Vec3::new(130.0, 0.0, 65.0),
+            white,
+        ),
+        0.01,
+        Color::new(1.0, 1.0, 1.0),
+    )));
+
+    cornell_scene(objects, light)
+}
+
+/// The floor, ceiling and three walls of the Cornell box.
+fn cornell_walls(white: Arc<dyn Material>) -> Vec<Arc<dyn Hittable>> {
+    let red = Arc::new(Lambertian::new(Color::new(0.65, 0.05, 0.05)));
+    let green = Arc::new(Lambertian::new(Color::new(0.12, 0.45, 0.15)));
+    vec![
+        Arc::new(AaRect::new(Plane::Yz, 0.0, 555.0, 0.0, 555.0, 555.0, green)),
+        Arc::new(AaRect::new(Plane::Yz, 0.0, 555.0, 0.0, 555.0, 0.0, red)),
+        Arc::new(AaRect::new(
+            Plane::Xz,
+            0.0,
+            555.0,
+            0.0,
+            555.0,
+            0.0,
+            white.clone(),
+        )),
+        Arc::new(AaRect::new(
+            Plane::Xz,
+            0.0,
+            555.0,
+            0.0,
+            555.0,
+            555.0,
+            white.clone(),
+        )),
+        Arc::new(AaRect::new(Plane::Xy, 0.0, 555.0, 0.0, 555.0, 555.0, white)),
+    ]
+}
+
+/// Frames the Cornell box from in front of its open side.
+fn cornell_scene(objects: Vec<Arc<dyn Hittable>>, light: Arc<dyn Hittable>) -> Scene {
+    let mut settings = RenderSettings::new(600, 600);
+    settings.samples_per_pixel = 200;
+
+    let camera = Camera::new(
+        Point3::new(278.0, 278.0, -800.0),
+        Point3::new(278.0, 278.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        settings.aspect_ratio(),
+        0.0,
+        10.0,
+    );
+
+    Scene {
+        camera,
+        world: Arc::new(BvhNode::new(objects, SplitHeuristic::Sah)),
+        lights: Some(light),
+        background: Background::Solid(Color::new(0.0, 0.0, 0.0)),
+        settings,
+    }
+}
+
+/// A block with a corner at the origin, turned about the Y axis and moved.
+fn rotated_block(
+    size: Vec3,
+    degrees: f64,
+    offset: Vec3,
+    material: Arc<dyn Material>,
+) -> Arc<dyn Hittable> {
+    let block = Arc::new(Cuboid::new(Point3::new(0.0, 0.0, 0.0), size, material));
+    Arc::new(Instance::new(
+        block,
+        Matrix4::translation(offset) * Matrix4::rotation(Vec3::new(0.0, 1.0, 0.0), degrees),
+    ))
+}
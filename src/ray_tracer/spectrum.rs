@@ -0,0 +1,110 @@
+use std::sync::OnceLock;
+
+use crate::{Color, Vec3};
+
+/// Shortest wavelength, in nanometers, sampled by spectral rendering.
+pub const LAMBDA_MIN: f64 = 380.0;
+/// Longest wavelength, in nanometers, sampled by spectral rendering.
+pub const LAMBDA_MAX: f64 = 780.0;
+
+/// The CIE 1931 color matching functions (x̄, ȳ, z̄) at `lambda` nm, using
+/// the multi-lobe Gaussian fit of Wyman, Sloan and Shirley (2013).
+pub fn cie_xyz(lambda: f64) -> Vec3 {
+    // A Gaussian with different widths either side of its peak.
+    let g = |mu: f64, sigma_low: f64, sigma_high: f64| {
+        let sigma = if lambda < mu { sigma_low } else { sigma_high };
+        let t = (lambda - mu) / sigma;
+        (-0.5 * t * t).exp()
+    };
+    Vec3::new(
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    )
+}
+
+/// Converts CIE XYZ to linear sRGB (D65).
+pub fn xyz_to_linear_srgb(xyz: Vec3) -> Color {
+    let (x, y, z) = (xyz.x(), xyz.y(), xyz.z());
+    Color::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+/// Value at `lambda` nm of a smooth spectrum for an RGB color.
+///
+/// The red, green and blue primaries become overlapping bands that sum to
+/// one everywhere, so greys map to flat spectra and reflectances in [0, 1]
+/// stay in [0, 1]. This is a simple upsampling, not an exact round trip.
+pub fn rgb_to_spectrum(rgb: Color, lambda: f64) -> f64 {
+    let smoothstep = |edge0: f64, edge1: f64| {
+        let t = ((lambda - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+        t * t * (3.0 - 2.0 * t)
+    };
+    let blue = 1.0 - smoothstep(480.0, 510.0);
+    let red = smoothstep(570.0, 600.0);
+    let green = 1.0 - blue - red;
+    red * rgb.x() + green * rgb.y() + blue * rgb.z()
+}
+
+/// Linear sRGB of one Monte Carlo sample of a spectrum: `radiance` at
+/// `lambda` nm, drawn with density `pdf` per nanometer.
+///
+/// Results are white balanced so a flat spectrum, like the upsampling of a
+/// grey, comes out grey with its own value.
+pub fn spectral_sample_to_rgb(lambda: f64, radiance: f64, pdf: f64) -> Color {
+    let (y_integral, white) = calibration();
+    let rgb = xyz_to_linear_srgb(cie_xyz(lambda) * (radiance / (pdf * y_integral)));
+    Color::new(
+        rgb.x() / white.x(),
+        rgb.y() / white.y(),
+        rgb.z() / white.z(),
+    )
+}
+
+/// The integral of ȳ over the sampled wavelengths, and the linear sRGB of a
+/// flat spectrum normalized by it.
+fn calibration() -> (f64, Color) {
+    static CALIBRATION: OnceLock<(f64, Color)> = OnceLock::new();
+    *CALIBRATION.get_or_init(|| {
+        let steps = 4000;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / steps as f64;
+        let xyz = (0..steps)
+            .map(|i| cie_xyz(LAMBDA_MIN + (i as f64 + 0.5) * step) * step)
+            .fold(Vec3::new(0.0, 0.0, 0.0), |sum, value| sum + value);
+        (xyz.y(), xyz_to_linear_srgb(xyz / xyz.y()))
+    })
+}
+
+#[test]
+fn can_convert_spectra_to_rgb() {
+    // The fitted ȳ peaks near 555 nm at about one.
+    assert!((cie_xyz(555.0).y() - 1.0).abs() < 0.01);
+    assert!(cie_xyz(555.0).y() > cie_xyz(500.0).y());
+
+    let grey = Color::new(0.5, 0.5, 0.5);
+    for lambda in [400.0, 495.0, 550.0, 585.0, 700.0] {
+        assert!((rgb_to_spectrum(grey, lambda) - 0.5).abs() < 1e-12);
+    }
+    let red = Color::new(1.0, 0.0, 0.0);
+    assert_eq!(rgb_to_spectrum(red, 650.0), 1.0);
+    assert_eq!(rgb_to_spectrum(red, 450.0), 0.0);
+
+    // A flat spectrum sampled uniformly averages to white.
+    let samples = 1000;
+    let pdf = 1.0 / (LAMBDA_MAX - LAMBDA_MIN);
+    let white = (0..samples)
+        .map(|i| {
+            let lambda = LAMBDA_MIN + (i as f64 + 0.5) / samples as f64 * (LAMBDA_MAX - LAMBDA_MIN);
+            spectral_sample_to_rgb(lambda, 1.0, pdf)
+        })
+        .fold(Color::new(0.0, 0.0, 0.0), |sum, rgb| sum + rgb)
+        / samples as f64;
+    assert!(
+        (white - Color::new(1.0, 1.0, 1.0)).length() < 1e-3,
+        "{:?}",
+        white
+    );
+}
@@ -1,48 +1,123 @@
-use std::rc::Rc;
+use std::{f64::consts::PI, sync::Arc};
 
-use crate::{HitRecord, Hittable, Material, Point3, Ray};
+use crate::{random, Aabb, HitRecord, Hittable, Material, Onb, Point3, Ray, Vec3};
 
 pub struct Sphere {
     pub center: Point3,
     pub radius: f64,
-    pub material: Rc<dyn Material>,
+    pub material: Arc<dyn Material>,
 }
 
 impl Hittable for Sphere {
     fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
-        let oc = ray.origin() - self.center;
-        let a = ray.direction().length_squared();
-        let half_b = oc.dot(ray.direction());
-        let c = oc.length_squared() - self.radius * self.radius;
+        hit_sphere(
+            self.center,
+            self.radius,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+            rec,
+        )
+    }
 
-        let discriminant = half_b * half_b - a * c;
-        if discriminant < 0.0 {
-            return false;
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3::new(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+
+    /// Uniform over the cone of directions the sphere subtends.
+    fn pdf_value(&self, origin: Point3, direction: Vec3) -> f64 {
+        let mut rec = HitRecord::new();
+        if !self.hit(Ray::new(origin, direction), 0.001, f64::INFINITY, &mut rec) {
+            return 0.0;
         }
 
-        let sqrtd = discriminant.sqrt();
+        let distance_squared = (self.center - origin).length_squared();
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared)
+            .max(0.0)
+            .sqrt();
+        let solid_angle = 2.0 * PI * (1.0 - cos_theta_max);
+        1.0 / solid_angle
+    }
+
+    fn random(&self, origin: Point3) -> Vec3 {
+        let direction = self.center - origin;
+        let distance_squared = direction.length_squared();
+        let uvw = Onb::from_w(direction);
+        uvw.local(random_to_sphere(self.radius, distance_squared))
+    }
+}
+
+/// Samples a direction around +Z within the cone subtended by a sphere of
+/// `radius` whose center is `distance_squared` away.
+fn random_to_sphere(radius: f64, distance_squared: f64) -> Vec3 {
+    let r1 = random();
+    let r2 = random();
+    let cos_theta_max = (1.0 - radius * radius / distance_squared).max(0.0).sqrt();
+    let z = 1.0 + r2 * (cos_theta_max - 1.0);
+
+    let phi = 2.0 * PI * r1;
+    let sin_theta = (1.0 - z * z).max(0.0).sqrt();
+    Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, z)
+}
+
+/// Intersects a sphere at `center`, shared with `MovingSphere`.
+pub(crate) fn hit_sphere(
+    center: Point3,
+    radius: f64,
+    material: &Arc<dyn Material>,
+    ray: Ray,
+    t_min: f64,
+    t_max: f64,
+    rec: &mut HitRecord,
+) -> bool {
+    let oc = ray.origin() - center;
+    let a = ray.direction().length_squared();
+    let half_b = oc.dot(ray.direction());
+    let c = oc.length_squared() - radius * radius;
+
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 {
+        return false;
+    }
+
+    let sqrtd = discriminant.sqrt();
 
-        // Find the nearest root that lies in the acceptable range
-        let mut root = (-half_b - sqrtd) / a;
+    // Find the nearest root that lies in the acceptable range
+    let mut root = (-half_b - sqrtd) / a;
+    if root < t_min || t_max < root {
+        root = (-half_b + sqrtd) / a;
         if root < t_min || t_max < root {
-            root = (-half_b + sqrtd) / a;
-            if root < t_min || t_max < root {
-                return false;
-            }
+            return false;
         }
+    }
 
-        rec.t = Some(root);
-        rec.p = Some(ray.at(rec.t.unwrap()));
-        let outward_normal = (rec.p.unwrap() - self.center) / self.radius;
-        rec.set_face_normal(&ray, outward_normal);
-        rec.material = Some(self.material.clone());
+    rec.t = Some(root);
+    rec.p = Some(ray.at(rec.t.unwrap()));
+    let outward_normal = (rec.p.unwrap() - center) / radius;
+    rec.set_face_normal(&ray, outward_normal);
+    rec.material = Some(material.clone());
+    rec.barycentric = None;
+    rec.uv = Some(Sphere::uv(outward_normal));
 
-        true
-    }
+    true
 }
 
 impl Sphere {
-    pub fn new(center: Point3, radius: f64, material: Rc<dyn Material>) -> Sphere {
+    /// Spherical coordinates of a point on the unit sphere: u is the angle
+    /// around the Y axis from X = -1, and v the angle from Y = -1, both
+    /// scaled to [0, 1].
+    pub fn uv(p: Point3) -> (f64, f64) {
+        let theta = (-p.y()).acos();
+        let phi = (-p.z()).atan2(p.x()) + std::f64::consts::PI;
+        (
+            phi / (2.0 * std::f64::consts::PI),
+            theta / std::f64::consts::PI,
+        )
+    }
+
+    pub fn new(center: Point3, radius: f64, material: Arc<dyn Material>) -> Sphere {
         Sphere {
             center,
             radius,
@@ -50,3 +125,13 @@ impl Sphere {
         }
     }
 }
+
+#[test]
+fn can_compute_sphere_uv() {
+    let close =
+        |(u, v): (f64, f64), (eu, ev): (f64, f64)| (u - eu).abs() < 1e-9 && (v - ev).abs() < 1e-9;
+    assert!(close(Sphere::uv(Point3::new(1.0, 0.0, 0.0)), (0.5, 0.5)));
+    assert!(close(Sphere::uv(Point3::new(0.0, 1.0, 0.0)), (0.5, 1.0)));
+    assert!(close(Sphere::uv(Point3::new(0.0, 0.0, 1.0)), (0.25, 0.5)));
+    assert!(close(Sphere::uv(Point3::new(-1.0, 0.0, 0.0)), (0.0, 0.5)));
+}
@@ -0,0 +1,247 @@
+use std::{io, path::Path, sync::Arc};
+
+use crate::{Color, Perlin, Point3};
+
+/// A color that varies over a surface.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color;
+}
+
+pub struct SolidColor {
+    pub color: Color,
+}
+
+impl SolidColor {
+    pub fn new(color: Color) -> SolidColor {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: f64, _v: f64, _p: Point3) -> Color {
+        self.color
+    }
+}
+
+/// Alternates between two textures in a 3D grid of cubes `scale` wide.
+pub struct CheckerTexture {
+    pub odd: Arc<dyn Texture>,
+    pub even: Arc<dyn Texture>,
+    pub scale: f64,
+}
+
+impl CheckerTexture {
+    pub fn new(scale: f64, even: Arc<dyn Texture>, odd: Arc<dyn Texture>) -> CheckerTexture {
+        CheckerTexture { odd, even, scale }
+    }
+
+    pub fn from_colors(scale: f64, even: Color, odd: Color) -> CheckerTexture {
+        CheckerTexture::new(
+            scale,
+            Arc::new(SolidColor::new(even)),
+            Arc::new(SolidColor::new(odd)),
+        )
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: f64, v: f64, p: Point3) -> Color {
+        let cell = |x: f64| (x / self.scale).floor() as i64;
+        if (cell(p.x()) + cell(p.y()) + cell(p.z())) % 2 == 0 {
+            self.even.value(u, v, p)
+        } else {
+            self.odd.value(u, v, p)
+        }
+    }
+}
+
+/// Texture looked up from an image by its (u, v) coordinates.
+pub struct ImageTexture {
+    width: usize,
+    height: usize,
+    /// Linear RGB, top row first.
+    pixels: Vec<Color>,
+}
+
+impl ImageTexture {
+    /// Loads a PNG or JPEG file, converting its sRGB values to linear.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<ImageTexture> {
+        let path = path.as_ref();
+        let image = image::open(path)
+            .map_err(|e| io::Error::other(format!("{}: {}", path.display(), e)))?
+            .into_rgb8();
+        Ok(ImageTexture::from_rgb8(
+            image.width() as usize,
+            image.height() as usize,
+            image.as_raw(),
+        ))
+    }
+
+    /// Builds a texture from sRGB-encoded 8-bit RGB data, top row first.
+    pub fn from_rgb8(width: usize, height: usize, data: &[u8]) -> ImageTexture {
+        assert_eq!(
+            data.len(),
+            width * height * 3,
+            "image data has the wrong size"
+        );
+        let decode = |c: u8| srgb_to_linear(c as f64 / 255.0);
+        let pixels = data
+            .chunks(3)
+            .map(|rgb| Color::new(decode(rgb[0]), decode(rgb[1]), decode(rgb[2])))
+            .collect();
+        ImageTexture {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: f64, v: f64, _p: Point3) -> Color {
+        if self.pixels.is_empty() {
+            // Debugging aid for missing texture data.
+            return Color::new(0.0, 1.0, 1.0);
+        }
+
+        // Clamp to [0, 1] and flip v so it runs up the image.
+        let u = u.clamp(0.0, 1.0);
+        let v = 1.0 - v.clamp(0.0, 1.0);
+
+        let i = ((u * self.width as f64) as usize).min(self.width - 1);
+        let j = ((v * self.height as f64) as usize).min(self.height - 1);
+        self.pixels[j * self.width + i]
+    }
+}
+
+/// Grey Perlin turbulence.
+pub struct NoiseTexture {
+    pub noise: Perlin,
+    /// Frequency of the noise; larger values give finer detail.
+    pub scale: f64,
+}
+
+impl NoiseTexture {
+    pub fn new(noise: Perlin, scale: f64) -> NoiseTexture {
+        NoiseTexture { noise, scale }
+    }
+}
+
+impl Texture for NoiseTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        Color::new(1.0, 1.0, 1.0) * self.noise.turb(p * self.scale, TURBULENCE_DEPTH).min(1.0)
+    }
+}
+
+/// Marble veins: sine bands along z, phase-shifted by turbulence.
+pub struct MarbleTexture {
+    pub noise: Perlin,
+    pub scale: f64,
+    /// How strongly turbulence distorts the bands.
+    pub turbulence: f64,
+    pub base: Color,
+    pub vein: Color,
+}
+
+impl MarbleTexture {
+    pub fn new(noise: Perlin, scale: f64, turbulence: f64, base: Color, vein: Color) -> Self {
+        MarbleTexture {
+            noise,
+            scale,
+            turbulence,
+            base,
+            vein,
+        }
+    }
+}
+
+impl Texture for MarbleTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let turb = self.noise.turb(p, TURBULENCE_DEPTH);
+        let t = 0.5 * (1.0 + (self.scale * p.z() + self.turbulence * turb).sin());
+        lerp(self.vein, self.base, t)
+    }
+}
+
+/// Wood grain: concentric rings around the y axis, perturbed by turbulence.
+pub struct WoodTexture {
+    pub noise: Perlin,
+    /// Number of rings per unit of distance from the axis.
+    pub scale: f64,
+    /// How strongly turbulence distorts the rings.
+    pub turbulence: f64,
+    pub light: Color,
+    pub dark: Color,
+}
+
+impl WoodTexture {
+    pub fn new(noise: Perlin, scale: f64, turbulence: f64, light: Color, dark: Color) -> Self {
+        WoodTexture {
+            noise,
+            scale,
+            turbulence,
+            light,
+            dark,
+        }
+    }
+}
+
+impl Texture for WoodTexture {
+    fn value(&self, _u: f64, _v: f64, p: Point3) -> Color {
+        let radius = (p.x() * p.x() + p.z() * p.z()).sqrt();
+        let rings = self.scale * radius + self.turbulence * self.noise.turb(p, TURBULENCE_DEPTH);
+        let t = rings - rings.floor();
+        // Sharpen the dark part of each ring.
+        lerp(self.light, self.dark, t * t)
+    }
+}
+
+const TURBULENCE_DEPTH: usize = 7;
+
+fn lerp(a: Color, b: Color, t: f64) -> Color {
+    a * (1.0 - t) + b * t
+}
+
+fn srgb_to_linear(x: f64) -> f64 {
+    if x <= 0.04045 {
+        x / 12.92
+    } else {
+        ((x + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[test]
+fn checker_alternates_in_3d() {
+    let checker =
+        CheckerTexture::from_colors(1.0, Color::new(1.0, 1.0, 1.0), Color::new(0.0, 0.0, 0.0));
+    let white = Color::new(1.0, 1.0, 1.0);
+    assert_eq!(checker.value(0.0, 0.0, Point3::new(0.5, 0.5, 0.5)), white);
+    assert_ne!(checker.value(0.0, 0.0, Point3::new(1.5, 0.5, 0.5)), white);
+    assert_ne!(checker.value(0.0, 0.0, Point3::new(-0.5, 0.5, 0.5)), white);
+    assert_eq!(checker.value(0.0, 0.0, Point3::new(1.5, -0.5, 0.5)), white);
+}
+
+#[test]
+fn image_texture_samples_by_uv() {
+    // 2x1 image: red on the left, white on the right.
+    let texture = ImageTexture::from_rgb8(2, 1, &[255, 0, 0, 255, 255, 255]);
+    let p = Point3::new(0.0, 0.0, 0.0);
+    assert_eq!(texture.value(0.25, 0.5, p), Color::new(1.0, 0.0, 0.0));
+    assert_eq!(texture.value(0.75, 0.5, p), Color::new(1.0, 1.0, 1.0));
+    assert_eq!(texture.value(7.0, -1.0, p), Color::new(1.0, 1.0, 1.0));
+}
+
+#[test]
+fn noise_textures_stay_between_their_colors() {
+    let white = Color::new(1.0, 1.0, 1.0);
+    let black = Color::new(0.0, 0.0, 0.0);
+    let marble = MarbleTexture::new(Perlin::with_seed(1), 4.0, 10.0, white, black);
+    let wood = WoodTexture::new(Perlin::with_seed(1), 8.0, 2.0, white, black);
+    for i in 0..50 {
+        let p = Point3::new(i as f64 * 0.29, i as f64 * 0.13, i as f64 * -0.41);
+        for texture in [&marble as &dyn Texture, &wood] {
+            let c = texture.value(0.0, 0.0, p);
+            assert!((0.0..=1.0).contains(&c.x()), "{:?}", c);
+        }
+    }
+}
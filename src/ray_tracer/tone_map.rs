@@ -0,0 +1,185 @@
+use crate::Color;
+
+/// Curve compressing scene radiance into the displayable [0, 1] range.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ToneMapOperator {
+    /// Scales so the white point maps to 1 and clips anything brighter.
+    Clamp,
+    /// Reinhard's L / (1 + L) on luminance; never reaches white.
+    Reinhard,
+    /// Reinhard with a white point that maps exactly to 1.
+    ReinhardExtended,
+    /// Narkowicz's fit of the ACES filmic curve, normalized to the white point.
+    Aces,
+}
+
+impl ToneMapOperator {
+    pub fn from_name(name: &str) -> Option<ToneMapOperator> {
+        match name {
+            "clamp" => Some(ToneMapOperator::Clamp),
+            "reinhard" => Some(ToneMapOperator::Reinhard),
+            "reinhard-extended" => Some(ToneMapOperator::ReinhardExtended),
+            "aces" => Some(ToneMapOperator::Aces),
+            _ => None,
+        }
+    }
+}
+
+/// Encoding applied to display-referred values before quantization.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TransferFunction {
+    /// Square root, i.e. gamma 2.0.
+    Gamma2,
+    /// The sRGB opto-electronic transfer function.
+    Srgb,
+}
+
+impl TransferFunction {
+    pub fn from_name(name: &str) -> Option<TransferFunction> {
+        match name {
+            "gamma2" => Some(TransferFunction::Gamma2),
+            "srgb" => Some(TransferFunction::Srgb),
+            _ => None,
+        }
+    }
+
+    pub fn encode(&self, x: f64) -> f64 {
+        match self {
+            TransferFunction::Gamma2 => x.sqrt(),
+            TransferFunction::Srgb => {
+                if x <= 0.003_130_8 {
+                    12.92 * x
+                } else {
+                    1.055 * x.powf(1.0 / 2.4) - 0.055
+                }
+            }
+        }
+    }
+}
+
+/// Post-process turning linear radiance into 8-bit display values.
+///
+/// The default reproduces the original clamp and gamma 2.0 output.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ToneMap {
+    /// Exposure adjustment in stops (EV).
+    pub exposure: f64,
+    /// Radiance, after exposure, that maps to display white.
+    pub white_point: f64,
+    pub operator: ToneMapOperator,
+    pub transfer: TransferFunction,
+}
+
+impl Default for ToneMap {
+    fn default() -> Self {
+        ToneMap {
+            exposure: 0.0,
+            white_point: 1.0,
+            operator: ToneMapOperator::Clamp,
+            transfer: TransferFunction::Gamma2,
+        }
+    }
+}
+
+impl ToneMap {
+    /// Maps linear radiance to linear display values in [0, 1].
+    pub fn apply(&self, color: Color) -> Color {
+        let c = color * 2f64.powf(self.exposure);
+        let c = Color::new(c.x().max(0.0), c.y().max(0.0), c.z().max(0.0));
+        let white = self.white_point;
+
+        let mapped = match self.operator {
+            ToneMapOperator::Clamp => c / white,
+            ToneMapOperator::Reinhard => scale_luminance(c, |l| l / (1.0 + l)),
+            ToneMapOperator::ReinhardExtended => {
+                scale_luminance(c, |l| l * (1.0 + l / (white * white)) / (1.0 + l))
+            }
+            ToneMapOperator::Aces => {
+                let aces = |x: f64| (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+                let scale = 1.0 / aces(white);
+                Color::new(
+                    aces(c.x()) * scale,
+                    aces(c.y()) * scale,
+                    aces(c.z()) * scale,
+                )
+            }
+        };
+
+        Color::new(
+            mapped.x().clamp(0.0, 1.0),
+            mapped.y().clamp(0.0, 1.0),
+            mapped.z().clamp(0.0, 1.0),
+        )
+    }
+
+    /// Tone maps, encodes and quantizes a color.
+    pub fn to_rgb8(&self, color: Color) -> [u8; 3] {
+        let mapped = self.apply(color);
+        let quantize = |x: f64| (256.0 * self.transfer.encode(x).clamp(0.0, 0.999)) as u8;
+        [
+            quantize(mapped.x()),
+            quantize(mapped.y()),
+            quantize(mapped.z()),
+        ]
+    }
+}
+
+/// Rec. 709 relative luminance.
+pub fn luminance(color: Color) -> f64 {
+    0.2126 * color.x() + 0.7152 * color.y() + 0.0722 * color.z()
+}
+
+/// Applies a curve to the luminance of a color, preserving its hue.
+fn scale_luminance(color: Color, curve: impl Fn(f64) -> f64) -> Color {
+    let l = luminance(color);
+    if l <= 0.0 {
+        return Color::new(0.0, 0.0, 0.0);
+    }
+    color * (curve(l) / l)
+}
+
+#[test]
+fn default_tone_map_matches_legacy_output() {
+    let tone_map = ToneMap::default();
+    for color in [
+        Color::new(0.0, 0.25, 1.0),
+        Color::new(0.01, 0.5, 4.0),
+        Color::new(0.7, 0.3, 0.123),
+    ] {
+        assert_eq!(tone_map.to_rgb8(color), color.to_rgb8(1));
+    }
+}
+
+#[test]
+fn operators_map_white_point_to_white() {
+    for operator in [
+        ToneMapOperator::Clamp,
+        ToneMapOperator::ReinhardExtended,
+        ToneMapOperator::Aces,
+    ] {
+        let tone_map = ToneMap {
+            white_point: 8.0,
+            operator,
+            ..ToneMap::default()
+        };
+        let white = tone_map.apply(Color::new(8.0, 8.0, 8.0));
+        assert!((white.x() - 1.0).abs() < 1e-9, "{:?}", operator);
+        let grey = tone_map.apply(Color::new(2.0, 2.0, 2.0));
+        assert!(grey.x() > 0.0 && grey.x() < 1.0, "{:?}", operator);
+    }
+
+    let tone_map = ToneMap {
+        exposure: 1.0,
+        operator: ToneMapOperator::Reinhard,
+        ..ToneMap::default()
+    };
+    assert!((tone_map.apply(Color::new(0.5, 0.5, 0.5)).x() - 0.5).abs() < 1e-9);
+}
+
+#[test]
+fn can_encode_srgb() {
+    let srgb = TransferFunction::Srgb;
+    assert_eq!(srgb.encode(0.0), 0.0);
+    assert!((srgb.encode(1.0) - 1.0).abs() < 1e-9);
+    assert!((srgb.encode(0.18) - 0.4614).abs() < 1e-4);
+}
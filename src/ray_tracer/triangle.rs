@@ -0,0 +1,125 @@
+use std::sync::Arc;
+
+use crate::{vec3::cross, Aabb, HitRecord, Hittable, Material, Point3, Ray, Vec3};
+
+/// Minimum thickness of a triangle's bounding box along any axis.
+const BOX_PADDING: f64 = 1e-4;
+
+pub struct Triangle {
+    pub v0: Point3,
+    pub v1: Point3,
+    pub v2: Point3,
+    pub material: Arc<dyn Material>,
+}
+
+impl Triangle {
+    pub fn new(v0: Point3, v1: Point3, v2: Point3, material: Arc<dyn Material>) -> Triangle {
+        Triangle {
+            v0,
+            v1,
+            v2,
+            material,
+        }
+    }
+}
+
+impl Hittable for Triangle {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let (t, b1, b2) = match intersect(ray, self.v0, self.v1, self.v2, t_min, t_max) {
+            Some(hit) => hit,
+            None => return false,
+        };
+
+        rec.t = Some(t);
+        rec.p = Some(ray.at(t));
+        let outward_normal = cross(&(self.v1 - self.v0), &(self.v2 - self.v0)).unit_vector();
+        rec.set_face_normal(&ray, outward_normal);
+        rec.material = Some(self.material.clone());
+        rec.barycentric = Some((b1, b2));
+        rec.uv = Some((b1, b2));
+
+        true
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(triangle_box(self.v0, self.v1, self.v2))
+    }
+}
+
+/// Möller–Trumbore ray/triangle intersection.
+///
+/// Returns the ray parameter and the barycentric coordinates (b1, b2) of the
+/// hit relative to `v1` and `v2`.
+pub fn intersect(
+    ray: Ray,
+    v0: Point3,
+    v1: Point3,
+    v2: Point3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    let edge1 = v1 - v0;
+    let edge2 = v2 - v0;
+    let pvec = cross(&ray.direction(), &edge2);
+    let det = edge1.dot(pvec);
+    if det.abs() < 1e-12 {
+        // The ray is parallel to the triangle.
+        return None;
+    }
+
+    let inv_det = 1.0 / det;
+    let tvec = ray.origin() - v0;
+    let b1 = tvec.dot(pvec) * inv_det;
+    if !(0.0..=1.0).contains(&b1) {
+        return None;
+    }
+
+    let qvec = cross(&tvec, &edge1);
+    let b2 = ray.direction().dot(qvec) * inv_det;
+    if b2 < 0.0 || b1 + b2 > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(qvec) * inv_det;
+    if t < t_min || t_max < t {
+        return None;
+    }
+
+    Some((t, b1, b2))
+}
+
+pub(crate) fn triangle_box(v0: Point3, v1: Point3, v2: Point3) -> Aabb {
+    let min = Point3::new(
+        v0.x().min(v1.x()).min(v2.x()),
+        v0.y().min(v1.y()).min(v2.y()),
+        v0.z().min(v1.z()).min(v2.z()),
+    );
+    let max = Point3::new(
+        v0.x().max(v1.x()).max(v2.x()),
+        v0.y().max(v1.y()).max(v2.y()),
+        v0.z().max(v1.z()).max(v2.z()),
+    );
+    let pad = Vec3::new(BOX_PADDING, BOX_PADDING, BOX_PADDING);
+    Aabb::new(min - pad, max + pad)
+}
+
+#[test]
+fn can_hit_triangle() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let triangle = Triangle::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(1.0, 0.0, 0.0),
+        Point3::new(0.0, 1.0, 0.0),
+        material,
+    );
+    let mut rec = HitRecord::new();
+    let ray = Ray::new(Point3::new(0.25, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+    assert!(triangle.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    assert_eq!(rec.t(), Some(1.0));
+    assert_eq!(rec.barycentric, Some((0.25, 0.5)));
+    assert_eq!(rec.normal, Some(Vec3::new(0.0, 0.0, 1.0)));
+    assert_eq!(rec.front_face, Some(true));
+
+    let miss = Ray::new(Point3::new(0.75, 0.5, 1.0), Vec3::new(0.0, 0.0, -1.0));
+    assert!(!triangle.hit(miss, 0.001, f64::INFINITY, &mut rec));
+}
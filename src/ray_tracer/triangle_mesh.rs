@@ -0,0 +1,176 @@
+use std::sync::Arc;
+
+use crate::{
+    triangle::{intersect, triangle_box},
+    vec3::cross,
+    Aabb, BvhNode, HitRecord, Hittable, Material, Point3, Ray, SplitHeuristic, Vec3,
+};
+
+/// Vertex buffers shared by every triangle of a mesh.
+///
+/// `normals` and `uvs` are either empty or hold one entry per position, and
+/// each face in `indices` refers to three positions.
+pub struct MeshData {
+    pub positions: Vec<Point3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<(f64, f64)>,
+    pub indices: Vec<[usize; 3]>,
+    pub material: Arc<dyn Material>,
+}
+
+/// Indexed triangle mesh with its own BVH over its faces.
+pub struct TriangleMesh {
+    data: Arc<MeshData>,
+    bvh: BvhNode,
+}
+
+impl TriangleMesh {
+    /// Panics if the buffers are inconsistent or the mesh has no faces.
+    pub fn new(data: MeshData) -> TriangleMesh {
+        let vertex_count = data.positions.len();
+        assert!(
+            data.normals.is_empty() || data.normals.len() == vertex_count,
+            "mesh needs one normal per vertex"
+        );
+        assert!(
+            data.uvs.is_empty() || data.uvs.len() == vertex_count,
+            "mesh needs one texture coordinate per vertex"
+        );
+        assert!(
+            data.indices.iter().flatten().all(|&i| i < vertex_count),
+            "mesh index out of range"
+        );
+
+        let data = Arc::new(data);
+        let bvh = BvhNode::new(Self::faces(&data), SplitHeuristic::Sah);
+        TriangleMesh { data, bvh }
+    }
+
+    pub fn data(&self) -> &MeshData {
+        &self.data
+    }
+
+    /// Returns every face as an individual hittable sharing this mesh's
+    /// buffers, so they can be placed in a larger BVH.
+    pub fn triangles(&self) -> Vec<Arc<dyn Hittable>> {
+        Self::faces(&self.data)
+    }
+
+    fn faces(data: &Arc<MeshData>) -> Vec<Arc<dyn Hittable>> {
+        (0..data.indices.len())
+            .map(|face| {
+                Arc::new(MeshTriangle {
+                    mesh: data.clone(),
+                    face,
+                }) as Arc<dyn Hittable>
+            })
+            .collect()
+    }
+}
+
+impl Hittable for TriangleMesh {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        self.bvh.hit(ray, t_min, t_max, rec)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.bvh.bounding_box()
+    }
+}
+
+/// A single face of a `TriangleMesh`.
+pub struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    face: usize,
+}
+
+impl Hittable for MeshTriangle {
+    fn hit(&self, ray: Ray, t_min: f64, t_max: f64, rec: &mut HitRecord) -> bool {
+        let [i0, i1, i2] = self.mesh.indices[self.face];
+        let (v0, v1, v2) = (
+            self.mesh.positions[i0],
+            self.mesh.positions[i1],
+            self.mesh.positions[i2],
+        );
+        let (t, b1, b2) = match intersect(ray, v0, v1, v2, t_min, t_max) {
+            Some(hit) => hit,
+            None => return false,
+        };
+        let b0 = 1.0 - b1 - b2;
+
+        rec.t = Some(t);
+        rec.p = Some(ray.at(t));
+        let geometric_normal = cross(&(v1 - v0), &(v2 - v0)).unit_vector();
+        rec.set_face_normal(&ray, geometric_normal);
+
+        // Smooth shading: interpolate the vertex normals, keeping them on the
+        // same side of the surface as the geometric normal.
+        if !self.mesh.normals.is_empty() {
+            let n = &self.mesh.normals;
+            let mut shading_normal = (b0 * n[i0] + b1 * n[i1] + b2 * n[i2]).unit_vector();
+            if shading_normal.dot(geometric_normal) < 0.0 {
+                shading_normal = -shading_normal;
+            }
+            rec.normal = if rec.front_face.unwrap() {
+                Some(shading_normal)
+            } else {
+                Some(-shading_normal)
+            };
+        }
+
+        rec.material = Some(self.mesh.material.clone());
+        rec.barycentric = Some((b1, b2));
+        rec.uv = if self.mesh.uvs.is_empty() {
+            Some((b1, b2))
+        } else {
+            let uv = &self.mesh.uvs;
+            Some((
+                b0 * uv[i0].0 + b1 * uv[i1].0 + b2 * uv[i2].0,
+                b0 * uv[i0].1 + b1 * uv[i1].1 + b2 * uv[i2].1,
+            ))
+        };
+
+        true
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let [i0, i1, i2] = self.mesh.indices[self.face];
+        Some(triangle_box(
+            self.mesh.positions[i0],
+            self.mesh.positions[i1],
+            self.mesh.positions[i2],
+        ))
+    }
+}
+
+#[test]
+fn mesh_interpolates_vertex_normals() {
+    let material = Arc::new(crate::Lambertian::new(crate::Color::new(0.5, 0.5, 0.5)));
+    let mesh = TriangleMesh::new(MeshData {
+        positions: vec![
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(1.0, 0.0, 0.0),
+            Point3::new(1.0, 1.0, 0.0),
+            Point3::new(0.0, 1.0, 0.0),
+        ],
+        normals: vec![
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+        ],
+        uvs: vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)],
+        indices: vec![[0, 1, 2], [0, 2, 3]],
+        material,
+    });
+    assert_eq!(mesh.triangles().len(), 2);
+
+    let mut rec = HitRecord::new();
+    let ray = Ray::new(Point3::new(0.5, 0.25, 1.0), Vec3::new(0.0, 0.0, -1.0));
+    assert!(mesh.hit(ray, 0.001, f64::INFINITY, &mut rec));
+    let normal = rec.normal.unwrap();
+    assert!(normal.x().abs() < 1e-9);
+    assert!((normal.z() - 1.0).abs() < 1e-9);
+    let (u, v) = rec.uv.unwrap();
+    assert!((u - 0.5).abs() < 1e-9 && (v - 0.25).abs() < 1e-9);
+}
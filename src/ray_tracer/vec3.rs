@@ -77,6 +77,11 @@ impl Vec3 {
     }
 
     pub fn to_color_string(&self, samples_per_pixel: usize) -> String {
+        let [r, g, b] = self.to_rgb8(samples_per_pixel);
+        format!("{} {} {}", r, g, b)
+    }
+
+    pub fn to_rgb8(&self, samples_per_pixel: usize) -> [u8; 3] {
         // Divide the color by the number of samples and gamma-correct for gamma=2.0.
         let scale = 1.0 / samples_per_pixel as f64;
         let r = (self.x() * scale).sqrt();
@@ -84,12 +89,11 @@ impl Vec3 {
         let b = (self.z() * scale).sqrt();
 
         // Write the translated [0,255] value of each color component.
-        format!(
-            "{} {} {}",
+        [
             (256.0 * clamp(r, 0.0, 0.999)) as u8,
             (256.0 * clamp(g, 0.0, 0.999)) as u8,
             (256.0 * clamp(b, 0.0, 0.999)) as u8,
-        )
+        ]
     }
 }
 